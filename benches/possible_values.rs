@@ -0,0 +1,125 @@
+//! Compare [`PossibleValuesFinder`] with the naive strategy of probing every
+//! (cell, candidate) pair with its own solver.
+//!
+//! Run with `cargo bench --bench possible_values`.
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sudoku_solver::{CandidateSet, Grid, PossibleValuesFinder, Puzzle, Value};
+
+/// Launch a fresh solver for each (cell, candidate) pair not yet seen in a
+/// solution.
+fn probe_each_candidate(puzzle: &Puzzle) -> Grid<CandidateSet> {
+    let mut pvs: Grid<CandidateSet> = Grid::default();
+    if puzzle.solutions().next().is_none() {
+        return pvs;
+    }
+    for row in 0..9 {
+        for col in 0..9 {
+            for val in 1..=9 {
+                let given = puzzle.get(row, col);
+                if pvs[row][col].contains(val) || given.is_some_and(|v| v.value() != val) {
+                    continue;
+                }
+                let mut pzl = puzzle.clone();
+                pzl.pin(row, col, Value::new(val));
+                if let Some(sol) = pzl.solutions().next() {
+                    for (ir, sol_row) in sol.iter().enumerate() {
+                        for (ic, &v) in sol_row.iter().enumerate() {
+                            pvs[ir][ic].insert(v);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    pvs
+}
+
+/// Average duration of `f` over enough runs to last about a second.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let cases = [
+        ("empty", Puzzle::default()),
+        (
+            "sparse, 19 clues",
+            Puzzle::from_arr([
+                [0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 6, 7, 3, 0, 0, 2, 0],
+                [0, 0, 0, 0, 8, 0, 0, 0, 0],
+                [0, 0, 5, 2, 6, 0, 0, 3, 0],
+                [4, 0, 0, 0, 0, 0, 9, 0, 0],
+                [0, 0, 0, 0, 0, 1, 0, 0, 0],
+                [0, 9, 0, 1, 7, 0, 3, 0, 0],
+                [0, 0, 7, 0, 0, 8, 0, 0, 0],
+                [0, 0, 0, 0, 0, 0, 0, 1, 0],
+            ]),
+        ),
+        (
+            "sparse, 17 clues",
+            Puzzle::from_arr([
+                [0, 0, 0, 0, 0, 0, 0, 1, 0],
+                [4, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 2, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 5, 0, 4, 0, 7],
+                [0, 0, 8, 0, 0, 0, 3, 0, 0],
+                [0, 0, 1, 0, 9, 0, 0, 0, 0],
+                [3, 0, 0, 4, 0, 0, 2, 0, 0],
+                [0, 5, 0, 1, 0, 0, 0, 0, 0],
+                [0, 0, 0, 8, 0, 6, 0, 0, 0],
+            ]),
+        ),
+        (
+            "well-posed, 21 clues",
+            Puzzle::from_arr([
+                [0, 5, 0, 0, 0, 0, 0, 0, 6],
+                [0, 0, 6, 7, 3, 0, 0, 2, 0],
+                [0, 0, 0, 0, 8, 0, 0, 0, 0],
+                [0, 0, 5, 2, 6, 0, 0, 3, 0],
+                [4, 0, 0, 0, 0, 0, 9, 0, 0],
+                [0, 0, 0, 0, 0, 1, 0, 0, 0],
+                [0, 9, 0, 1, 7, 0, 3, 0, 0],
+                [0, 0, 7, 0, 0, 8, 0, 0, 0],
+                [0, 0, 0, 0, 0, 5, 0, 1, 0],
+            ]),
+        ),
+    ];
+    let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    println!(
+        "{:<24} {:>14} {:>14} {:>8} {:>14}",
+        "puzzle",
+        "probing",
+        "shared",
+        "speedup",
+        format!("{n_threads} threads"),
+    );
+    for (name, puzzle) in cases {
+        let shared = PossibleValuesFinder::new().search(&puzzle).completed();
+        assert_eq!(shared.as_ref(), Some(&probe_each_candidate(&puzzle)));
+        let t_probe = time(|| probe_each_candidate(&puzzle));
+        let t_shared = time(|| PossibleValuesFinder::new().search(&puzzle));
+        let t_parallel = time(|| {
+            PossibleValuesFinder::new()
+                .with_threads(n_threads)
+                .search(&puzzle)
+        });
+        println!(
+            "{:<24} {:>14?} {:>14?} {:>7.1}x {:>14?}",
+            name,
+            t_probe,
+            t_shared,
+            t_probe.as_secs_f64() / t_shared.as_secs_f64(),
+            t_parallel,
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::cmp::Reverse;
+
+use crate::{CandidateSet, Edit, Grid, Puzzle, Value};
+
+impl Puzzle {
+    /// Suggest givens to add so that the puzzle has a single solution, `None`
+    /// if it has no solution, and nothing if it already has a single one.
+    ///
+    /// Givens are added one by one in the cell with the most possible values,
+    /// see [`Self::possible_values`], all from the same solution.  Those that
+    /// later ones make unnecessary are then dropped, so that no suggestion
+    /// can be left out.  This is not always the smallest such set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Edit, Geometry, Puzzle};
+    ///
+    /// let mut pzl = Puzzle::new(Geometry::new(2, 2));
+    /// let edits = pzl.suggest_givens().unwrap();
+    /// for edit in edits {
+    ///     assert!(matches!(edit, Edit::Pin(..)));
+    ///     pzl.apply(edit);
+    /// }
+    /// assert_eq!(pzl.solutions().count(), 1);
+    /// ```
+    pub fn suggest_givens(&self) -> Option<Vec<Edit>> {
+        let target = self.solutions().next()?;
+        let mut puzzle = self.clone();
+        let mut added = Vec::new();
+        while puzzle.solutions().take(2).count() > 1 {
+            let possible = puzzle.possible_values();
+            let ((row, col), _) = possible
+                .cells()
+                .min_by_key(|(_, values)| Reverse(values.len()))
+                .expect("grids are not empty");
+            let val = Value::new(target[row][col]);
+            puzzle.pin(row, col, val);
+            added.push(Edit::Pin(row, col, val));
+        }
+        // Givens added early may not be needed with the later ones.
+        let mut needed = Vec::new();
+        for edit in added {
+            let Edit::Pin(row, col, _) = edit else {
+                unreachable!("only givens are added");
+            };
+            puzzle.unpin(row, col);
+            if puzzle.solutions().take(2).count() > 1 {
+                puzzle.apply(edit);
+                needed.push(edit);
+            }
+        }
+        Some(needed)
+    }
+
+    /// The backbone of the puzzle: the value of each cell that is the same in
+    /// all the solutions, givens included.  This is derived from
+    /// [`Self::possible_values`], and is empty for a puzzle without solution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::Puzzle;
+    ///
+    /// let pzl = Puzzle::from_line("123434122.4.4.2.").unwrap();
+    /// let backbone = pzl.backbone();
+    /// assert_eq!(backbone[2][0], Some(2));
+    /// assert_eq!(backbone[2][1], None);
+    /// ```
+    pub fn backbone(&self) -> Grid<Option<u8>> {
+        self.possible_values().map(CandidateSet::single)
+    }
+
+    /// The essential givens of a puzzle with a single solution, in reading
+    /// order: those without which it would have several solutions.  The other
+    /// givens are redundant one at a time, but removing several of them may
+    /// still lose uniqueness.  This is `None` if the puzzle does not have a
+    /// single solution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::Puzzle;
+    ///
+    /// // Every given of a full grid follows from the others.
+    /// let pzl = Puzzle::from_line("1234341221434321").unwrap();
+    /// assert_eq!(pzl.essential_givens(), Some(Vec::new()));
+    /// let pzl = Puzzle::from_line("1...............").unwrap();
+    /// assert_eq!(pzl.essential_givens(), None);
+    /// ```
+    pub fn essential_givens(&self) -> Option<Vec<(usize, usize)>> {
+        if self.solutions().take(2).count() != 1 {
+            return None;
+        }
+        let size = self.geometry().size();
+        let cells = (0..size).flat_map(|row| (0..size).map(move |col| (row, col)));
+        let essential = cells
+            .filter(|&(row, col)| {
+                self.get(row, col).is_some() && {
+                    let mut less = self.clone();
+                    less.unpin(row, col);
+                    less.solutions().take(2).count() > 1
+                }
+            })
+            .collect();
+        Some(essential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::classic_puzzle;
+    use crate::Geometry;
+
+    #[test]
+    fn suggestions_are_needed() {
+        let full = classic_puzzle();
+        let mut pzl = full.clone();
+        for (row, col) in [(0, 0), (1, 3), (4, 0), (7, 4), (8, 7), (8, 8)] {
+            pzl.unpin(row, col);
+        }
+        assert!(pzl.solutions().take(2).count() > 1);
+        let edits = pzl.suggest_givens().unwrap();
+        assert!(!edits.is_empty());
+        for edit in &edits {
+            pzl.apply(*edit);
+        }
+        assert_eq!(pzl.solutions().take(2).count(), 1);
+        for edit in edits {
+            let Edit::Pin(row, col, _) = edit else {
+                panic!("suggestions are givens");
+            };
+            let mut less = pzl.clone();
+            less.unpin(row, col);
+            assert_eq!(less.solutions().take(2).count(), 2);
+        }
+    }
+
+    #[test]
+    fn backbone_of_ambiguous_puzzle() {
+        let pzl = Puzzle::from_line("123434122.4.4.2.").unwrap();
+        let backbone = pzl.backbone();
+        for ((row, col), &val) in backbone.cells() {
+            let ambiguous = [1, 3].contains(&col) && row >= 2;
+            assert_eq!(val.is_none(), ambiguous, "r{row}c{col}");
+        }
+        let pzl = Puzzle::from_line("11..............").unwrap();
+        assert!(pzl.backbone().cells().all(|(_, val)| val.is_none()));
+    }
+
+    #[test]
+    fn essential_and_redundant_givens() {
+        let full = classic_puzzle();
+        let essential = full.essential_givens().unwrap();
+        let minimal = full.minimized();
+        let size = 9;
+        let givens: Vec<_> = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter(|&(row, col)| minimal.get(row, col).is_some())
+            .collect();
+        assert_eq!(minimal.essential_givens(), Some(givens));
+        for (row, col) in essential {
+            let mut less = full.clone();
+            less.unpin(row, col);
+            assert!(less.solutions().take(2).count() > 1);
+        }
+    }
+
+    #[test]
+    fn nothing_to_suggest() {
+        let pzl = classic_puzzle();
+        assert_eq!(pzl.suggest_givens(), Some(Vec::new()));
+        let pzl = Puzzle::from_line("11..............").unwrap();
+        assert_eq!(pzl.suggest_givens(), None);
+        let pzl = Puzzle::new(Geometry::new(2, 3));
+        let edits = pzl.suggest_givens().unwrap();
+        assert!(edits.len() < 36);
+    }
+}
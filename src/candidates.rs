@@ -0,0 +1,566 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign};
+use std::sync::Arc;
+
+use crate::soft::{all_on, SoftConstraint};
+use crate::solgrid::{Layout, SolutionGrid};
+use crate::{Grid, PossibleValuesFinder, Puzzle, SolutionIterator, Technique, Value, MAX_SIZE};
+
+/// A set of values of a cell, from 1 to [`MAX_SIZE`], such as its candidates
+/// or its possible values.  This is a bitmask: it is `Copy` and never
+/// allocates.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::CandidateSet;
+///
+/// let mut set: CandidateSet = [1, 3, 4].into();
+/// assert!(set.remove(3));
+/// assert_eq!(set.len(), 2);
+/// let other = CandidateSet::from([2, 4]);
+/// assert_eq!(set | other, [1, 2, 4].into());
+/// assert_eq!(set & other, [4].into());
+/// assert_eq!(set - other, [1].into());
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [1, 4]);
+/// assert_eq!(format!("{set:?}"), "{1, 4}");
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CandidateSet(u32);
+
+impl CandidateSet {
+    /// The empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// All the values of a grid of a given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is larger than [`MAX_SIZE`].
+    pub fn full(size: usize) -> Self {
+        assert!(size <= MAX_SIZE, "Grid size is at most {MAX_SIZE}.");
+        Self(all_on(size))
+    }
+
+    /// Build from a bitmask as returned by [`Self::bits`], `None` if bits
+    /// beyond the [`MAX_SIZE`]-th are set.
+    pub const fn from_bits(bits: u32) -> Option<Self> {
+        if bits & !all_on(MAX_SIZE) == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    /// The bitmask of the values, the lowest bit standing for 1.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// The number of values.
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Whether there are no values.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether a value is in the set, `false` for values out of range.
+    pub const fn contains(self, val: u8) -> bool {
+        val > 0 && val as usize <= MAX_SIZE && self.0 & (1 << (val - 1)) != 0
+    }
+
+    /// Add a value, returning whether it was not there yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `val` is not between 1 and [`MAX_SIZE`] inclusive.
+    pub fn insert(&mut self, val: u8) -> bool {
+        let bit = Self::from(Value::new(val)).0;
+        let added = self.0 & bit == 0;
+        self.0 |= bit;
+        added
+    }
+
+    /// Remove a value, returning whether it was there.
+    pub fn remove(&mut self, val: u8) -> bool {
+        let removed = self.contains(val);
+        if removed {
+            self.0 &= !(1 << (val - 1));
+        }
+        removed
+    }
+
+    /// The smallest value, if any.
+    pub const fn first(self) -> Option<u8> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as u8 + 1)
+        }
+    }
+
+    /// The largest value, if any.
+    pub const fn last(self) -> Option<u8> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(32 - self.0.leading_zeros() as u8)
+        }
+    }
+
+    /// The value of a set with a single one, `None` otherwise.
+    pub const fn single(self) -> Option<u8> {
+        if self.0.count_ones() == 1 {
+            self.first()
+        } else {
+            None
+        }
+    }
+
+    /// Whether all the values of `self` are in `other`.
+    pub const fn is_subset(self, other: Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Whether `self` and `other` have no values in common.
+    pub const fn is_disjoint(self, other: Self) -> bool {
+        self.0 & other.0 == 0
+    }
+
+    /// Iterate through the values, in increasing order.
+    pub fn iter(self) -> CandidateIter {
+        CandidateIter(self.0)
+    }
+}
+
+impl fmt::Debug for CandidateSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl BitOr for CandidateSet {
+    type Output = Self;
+
+    /// The union of two sets.
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for CandidateSet {
+    type Output = Self;
+
+    /// The intersection of two sets.
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Sub for CandidateSet {
+    type Output = Self;
+
+    /// The values of `self` that are not in `rhs`.
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
+
+impl BitOrAssign for CandidateSet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
+
+impl BitAndAssign for CandidateSet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs;
+    }
+}
+
+impl SubAssign for CandidateSet {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Iterator through the values of a [`CandidateSet`], in increasing order.
+#[derive(Clone, Debug)]
+pub struct CandidateIter(u32);
+
+impl Iterator for CandidateIter {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let val = CandidateSet(self.0).first()?;
+        self.0 &= self.0 - 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for CandidateIter {
+    fn next_back(&mut self) -> Option<u8> {
+        let val = CandidateSet(self.0).last()?;
+        self.0 &= !(1 << (val - 1));
+        Some(val)
+    }
+}
+
+impl ExactSizeIterator for CandidateIter {}
+
+impl FusedIterator for CandidateIter {}
+
+impl IntoIterator for CandidateSet {
+    type Item = u8;
+    type IntoIter = CandidateIter;
+
+    fn into_iter(self) -> CandidateIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<u8> for CandidateSet {
+    /// Collect values into a set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is not between 1 and [`MAX_SIZE`] inclusive.
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u8> for CandidateSet {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(val);
+        }
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for CandidateSet {
+    fn from(values: [u8; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl From<Value> for CandidateSet {
+    fn from(val: Value) -> Self {
+        Self(1 << (val.value() - 1))
+    }
+}
+
+impl From<CandidateSet> for BTreeSet<u8> {
+    fn from(set: CandidateSet) -> Self {
+        set.iter().collect()
+    }
+}
+
+impl From<SoftConstraint> for CandidateSet {
+    fn from(sc: SoftConstraint) -> Self {
+        Self(sc.bits())
+    }
+}
+
+impl From<CandidateSet> for SoftConstraint {
+    fn from(set: CandidateSet) -> Self {
+        SoftConstraint::from_bits(set.0).expect("values are in range")
+    }
+}
+
+/// The candidates of each cell of a [`Puzzle`], as pencil marks in the
+/// course of solving it.
+///
+/// The givens of the puzzle are their only candidate and cannot be edited.
+/// The candidates of the other cells are freely added and removed, a cell
+/// with a single candidate standing for a filled cell.  Solutions and hints
+/// only consider the values left as candidates.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{CandidateGrid, Puzzle, Value};
+///
+/// let pzl = Puzzle::from_line("1...............").unwrap();
+/// let mut cands = CandidateGrid::new(&pzl);
+/// assert_eq!(cands.candidates(0, 1).len(), 4);
+/// cands.auto_fill();
+/// assert_eq!(cands.candidates(0, 1), [2, 3, 4].into());
+/// cands.remove(0, 1, Value::new(2));
+/// cands.remove(0, 1, Value::new(3));
+/// assert!(cands.solutions().all(|sol| sol[0][1] == 4));
+/// ```
+#[derive(Clone)]
+pub struct CandidateGrid {
+    puzzle: Puzzle,
+    candidates: Grid<SoftConstraint>,
+}
+
+impl CandidateGrid {
+    /// The candidates of a [`Puzzle`] before any deduction: all the values in
+    /// the cells without a given.
+    pub fn new(puzzle: &Puzzle) -> Self {
+        let size = puzzle.geometry().size();
+        let candidates = Grid::from_fn(size, |row, col| match puzzle.get(row, col) {
+            Some(val) => val.into(),
+            None => SoftConstraint::all(size),
+        });
+        Self {
+            puzzle: puzzle.clone(),
+            candidates,
+        }
+    }
+
+    /// The puzzle whose candidates these are.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// The candidates of a cell.
+    pub fn candidates(&self, row: usize, col: usize) -> CandidateSet {
+        self.candidates[row][col].into()
+    }
+
+    /// Whether a value is a candidate of a cell.
+    pub fn contains(&self, row: usize, col: usize, val: Value) -> bool {
+        self.candidates[row][col].has_solution(val)
+    }
+
+    /// Add a candidate to a cell, doing nothing for a given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is larger than the size of the grid.
+    pub fn add(&mut self, row: usize, col: usize, val: Value) {
+        assert!(
+            val.value() as usize <= self.candidates.size(),
+            "Value out of range."
+        );
+        if self.puzzle.get(row, col).is_none() {
+            self.candidates[row][col].allow(val);
+        }
+    }
+
+    /// Remove a candidate from a cell, doing nothing for a given.
+    pub fn remove(&mut self, row: usize, col: usize, val: Value) {
+        if self.puzzle.get(row, col).is_none() {
+            self.candidates[row][col].forbid(val);
+        }
+    }
+
+    /// Reset the candidates of the cells without a given to the values that
+    /// no given in a row, column, region, or other group of distinct values
+    /// rules out.
+    pub fn auto_fill(&mut self) {
+        let size = self.candidates.size();
+        let mut seen = Grid::filled(size, SoftConstraint::empty());
+        for group in self.puzzle.constraints().flat_map(|c| c.groups(size)) {
+            let mut givens = SoftConstraint::empty();
+            for &(row, col) in &group {
+                if let Some(val) = self.puzzle.get(row, col) {
+                    givens.allow(val);
+                }
+            }
+            for (row, col) in group {
+                seen[row][col] = SoftConstraint::from_bits(seen[row][col].bits() | givens.bits())
+                    .expect("values are in range");
+            }
+        }
+        for ((row, col), &seen) in seen.cells() {
+            if self.puzzle.get(row, col).is_none() {
+                self.candidates[row][col] = SoftConstraint::all(size).difference(seen);
+            }
+        }
+    }
+
+    /// Apply the easiest [`Technique`] of [`Puzzle::rate`] that removes
+    /// candidates, and return it, or `None` if none does.
+    ///
+    /// A cell with a single candidate counts as filled: removing its value
+    /// from the cells that see it is a [`Technique::NakedSingle`].  Guesses
+    /// and the techniques assuming a single solution are never used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{CandidateGrid, Puzzle, Technique};
+    ///
+    /// let pzl = Puzzle::from_line(
+    ///     "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    /// ).unwrap();
+    /// let mut cands = CandidateGrid::new(&pzl);
+    /// cands.auto_fill();
+    /// while let Some(technique) = cands.apply_hint() {
+    ///     assert!(technique <= Technique::HiddenSingle);
+    /// }
+    /// assert_eq!(cands.solutions().count(), 1);
+    /// assert!((0..9).all(|row| (0..9).all(|col| cands.candidates(row, col).len() == 1)));
+    /// ```
+    pub fn apply_hint(&mut self) -> Option<Technique> {
+        crate::rating::hint(&self.puzzle, &mut self.candidates)
+    }
+
+    /// Create an iterator through the solutions of the puzzle that only use
+    /// the candidates of each cell.
+    pub fn solutions(&self) -> SolutionIterator {
+        SolutionIterator::from_candidates(self)
+    }
+
+    /// Compute the set of candidates in each cell that lead to a solution,
+    /// like [`Puzzle::possible_values`].
+    pub fn possible_values(&self) -> Grid<CandidateSet> {
+        PossibleValuesFinder::new()
+            .search_candidates(self)
+            .completed()
+            .expect("search without handle nor budget always completes")
+    }
+
+    /// The propagated grid the solver starts from, `None` if it has no
+    /// solution.
+    pub(crate) fn root(&self, layout: Arc<Layout>) -> Option<SolutionGrid> {
+        let mut root = SolutionGrid::from_puzzle(layout, &self.puzzle).ok()?;
+        root.restrict(&self.candidates).ok()?;
+        Some(root)
+    }
+}
+
+impl From<&Puzzle> for CandidateGrid {
+    fn from(puzzle: &Puzzle) -> Self {
+        Self::new(puzzle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::classic_puzzle;
+    use crate::{Diagonals, Geometry};
+
+    #[test]
+    fn set_operations() {
+        let mut set = CandidateSet::new();
+        assert!(set.is_empty() && set.first().is_none() && set.single().is_none());
+        assert!(set.insert(3) && !set.insert(3));
+        assert_eq!(set.single(), Some(3));
+        set |= [1, 9].into();
+        assert_eq!((set.len(), set.first(), set.last()), (3, Some(1), Some(9)));
+        assert!(!set.contains(0) && !set.contains(MAX_SIZE as u8 + 1));
+        assert!(!set.remove(2) && set.remove(9) && !set.remove(0));
+        assert!(set.is_subset(CandidateSet::full(4)) && !set.is_subset([1].into()));
+        assert!(set.is_disjoint([2, 4].into()));
+        set &= [1, 2].into();
+        assert_eq!(set, [1].into());
+        set -= [1].into();
+        assert!(set.is_empty());
+        assert_eq!(CandidateSet::full(9).bits(), 0x1ff);
+        assert_eq!(CandidateSet::from_bits(0b101), Some([1, 3].into()));
+        assert_eq!(CandidateSet::from_bits(1 << MAX_SIZE), None);
+        assert_eq!(CandidateSet::from(Value::new(2)), [2].into());
+    }
+
+    #[test]
+    fn set_iteration_and_conversions() {
+        let set: CandidateSet = [4, 2, 7].into_iter().collect();
+        let iter = set.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.rev().collect::<Vec<_>>(), [7, 4, 2]);
+        let mut iter = set.into_iter();
+        assert_eq!((iter.next(), iter.next_back()), (Some(2), Some(7)));
+        assert_eq!(iter.collect::<Vec<_>>(), [4]);
+        assert_eq!(BTreeSet::from(set), BTreeSet::from([2, 4, 7]));
+        assert_eq!(format!("{:?}", CandidateSet::new()), "{}");
+        let sc = SoftConstraint::from(set);
+        assert_eq!(CandidateSet::from(sc), set);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_range() {
+        CandidateSet::new().insert(0);
+    }
+
+    #[test]
+    fn givens_cannot_be_edited() {
+        let pzl = classic_puzzle();
+        let mut cands = CandidateGrid::new(&pzl);
+        cands.remove(0, 0, Value::new(5));
+        cands.add(0, 0, Value::new(1));
+        assert_eq!(cands.candidates(0, 0), [5].into());
+        cands.remove(0, 2, Value::new(1));
+        assert!(!cands.contains(0, 2, Value::new(1)));
+        cands.add(0, 2, Value::new(1));
+        assert_eq!(cands.candidates(0, 2).len(), 9);
+    }
+
+    #[test]
+    fn auto_fill_uses_all_groups() {
+        let mut pzl = Puzzle::new(Geometry::new(2, 2)).with_constraint(Diagonals);
+        pzl.pin(0, 0, Value::new(1));
+        let mut cands = CandidateGrid::new(&pzl);
+        cands.remove(2, 3, Value::new(1));
+        cands.auto_fill();
+        assert_eq!(cands.candidates(3, 3), [2, 3, 4].into());
+        assert_eq!(cands.candidates(3, 0), [2, 3, 4].into());
+        assert_eq!(cands.candidates(2, 3), [1, 2, 3, 4].into());
+        assert_eq!(cands.candidates(2, 2), [2, 3, 4].into());
+        assert_eq!(cands.candidates(2, 1).len(), 4);
+    }
+
+    #[test]
+    fn solutions_follow_candidates() {
+        let pzl = Puzzle::new(Geometry::new(2, 2));
+        let mut cands = CandidateGrid::from(&pzl);
+        assert_eq!(cands.solutions().count(), 288);
+        for val in 2..=4 {
+            cands.remove(0, 0, Value::new(val));
+        }
+        assert_eq!(cands.solutions().count(), 72);
+        assert!(cands.solutions().all(|sol| sol[0][0] == 1));
+        let possible = cands.possible_values();
+        assert_eq!(possible[0][0], [1].into());
+        assert_eq!(possible[0][1], [2, 3, 4].into());
+        cands.remove(0, 0, Value::new(1));
+        assert_eq!(cands.solutions().count(), 0);
+        assert!(cands
+            .possible_values()
+            .cells()
+            .all(|(_, vals)| vals.is_empty()));
+    }
+
+    #[test]
+    fn hints_keep_the_solution() {
+        let pzl = classic_puzzle();
+        let solution = pzl.solutions().next().unwrap();
+        let mut cands = CandidateGrid::new(&pzl);
+        // Filling a cell is reported as a naked single.
+        cands.auto_fill();
+        for val in [1, 2] {
+            cands.remove(0, 2, Value::new(val));
+        }
+        assert!(cands.contains(0, 5, Value::new(4)));
+        assert_eq!(cands.apply_hint(), Some(Technique::NakedSingle));
+        assert!(!cands.contains(0, 5, Value::new(4)));
+        while cands.apply_hint().is_some() {
+            for ((row, col), &val) in solution.cells() {
+                assert!(cands.contains(row, col, Value::new(val)));
+            }
+        }
+        assert!(solution
+            .cells()
+            .all(|((row, col), &val)| cands.candidates(row, col) == [val].into()));
+    }
+}
@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+
+use crate::{Grid, Puzzle, Regions};
+
+/// All the orders of `n` items.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|perm| {
+            (0..n).map(move |i| {
+                let mut perm = perm.clone();
+                perm.insert(i, n - 1);
+                perm
+            })
+        })
+        .collect()
+}
+
+/// All the orders of `groups * len` lines that keep groups of `len`
+/// consecutive lines together, like the rows of the bands of a grid.
+fn line_orders(groups: usize, len: usize) -> Vec<Vec<usize>> {
+    let inner = permutations(len);
+    permutations(groups)
+        .into_iter()
+        .flat_map(|group_order| {
+            group_order
+                .into_iter()
+                .fold(vec![Vec::new()], |orders, group| {
+                    orders
+                        .into_iter()
+                        .flat_map(|order: Vec<usize>| {
+                            inner.iter().map(move |lines| {
+                                let mut order = order.clone();
+                                order.extend(lines.iter().map(|line| group * len + line));
+                                order
+                            })
+                        })
+                        .collect()
+                })
+        })
+        .collect()
+}
+
+/// Replace `best` by the givens read with the rows and columns in a given
+/// order, with values numbered in order of appearance, if that is smaller.
+fn keep_smallest(givens: &Grid<u8>, rows: &[usize], cols: &[usize], best: &mut [u8]) {
+    let mut labels = [0; 33];
+    let mut next = 0;
+    let mut smaller = false;
+    let cells = rows.iter().flat_map(|&r| cols.iter().map(move |&c| (r, c)));
+    for ((row, col), best) in cells.zip(best) {
+        let val = givens[row][col];
+        let label = if val == 0 {
+            0
+        } else {
+            if labels[val as usize] == 0 {
+                next += 1;
+                labels[val as usize] = next;
+            }
+            labels[val as usize]
+        };
+        if !smaller {
+            match label.cmp(best) {
+                Ordering::Greater => return,
+                Ordering::Less => smaller = true,
+                Ordering::Equal => continue,
+            }
+        }
+        *best = label;
+    }
+}
+
+impl Puzzle {
+    /// The canonical form of a classic puzzle: the smallest in reading order,
+    /// with 0 for empty cells, of the equivalent puzzles obtained by swapping
+    /// bands, stacks, rows in a band, columns in a stack, relabelling values,
+    /// and transposing when boxes are square.  Equivalent puzzles have the
+    /// same canonical form.
+    ///
+    /// This is `None` for puzzles with other regions than boxes or with
+    /// additional constraints, and for grids larger than 9x9, whose
+    /// symmetries are too many to go through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::Puzzle;
+    ///
+    /// let pzl = Puzzle::from_line("1...........4...").unwrap();
+    /// // The same puzzle, transposed and with other values.
+    /// let other = Puzzle::from_line("3.......2.......").unwrap();
+    /// let canonical = pzl.canonical().unwrap();
+    /// assert_eq!(canonical.to_line(), other.canonical().unwrap().to_line());
+    /// assert_eq!(canonical.to_line(), ".............1.2");
+    /// ```
+    pub fn canonical(&self) -> Option<Puzzle> {
+        let geometry = self.geometry();
+        let size = geometry.size();
+        // Beyond the latin square and the regions.
+        let extra = self.constraints().nth(2).is_some();
+        if size > 9 || extra || *self.regions() != Regions::boxes(geometry) {
+            return None;
+        }
+        let givens = Grid::from_fn(size, |row, col| self.get(row, col).map_or(0, |v| v.value()));
+        let mut candidates = vec![givens.clone()];
+        if geometry.box_rows() == geometry.box_cols() {
+            candidates.push(Grid::from_fn(size, |row, col| givens[col][row]));
+        }
+        // Bands are `box_rows` rows high, and stacks `box_cols` columns wide.
+        let rows = line_orders(geometry.box_cols(), geometry.box_rows());
+        let cols = line_orders(geometry.box_rows(), geometry.box_cols());
+        let mut best = vec![u8::MAX; size * size];
+        for givens in &candidates {
+            for row_order in &rows {
+                for col_order in &cols {
+                    keep_smallest(givens, row_order, col_order, &mut best);
+                }
+            }
+        }
+        let canonical = Grid::from_fn(size, |row, col| best[row * size + col]);
+        Some(Puzzle::from_grid(geometry, canonical))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::classic_puzzle;
+    use crate::Geometry;
+
+    #[test]
+    fn orders() {
+        assert_eq!(permutations(3).len(), 6);
+        let orders = line_orders(2, 2);
+        assert_eq!(orders.len(), 8);
+        assert!(orders.contains(&vec![3, 2, 0, 1]));
+        assert!(!orders.contains(&vec![0, 2, 1, 3]));
+    }
+
+    #[test]
+    fn equivalent_puzzles() {
+        let pzl = classic_puzzle();
+        let canonical = pzl.canonical().unwrap();
+        // Swap the first two bands, transpose, and swap values 1 and 9.
+        let shuffled = Grid::from_fn(9, |row, col| {
+            let (row, col) = (col, if row < 6 { (row + 3) % 6 } else { row });
+            match pzl.get(row, col).map_or(0, |v| v.value()) {
+                1 => 9,
+                9 => 1,
+                v => v,
+            }
+        });
+        let shuffled = Puzzle::from_grid(Geometry::CLASSIC, shuffled);
+        assert_ne!(shuffled.to_line(), pzl.to_line());
+        assert_eq!(shuffled.canonical().unwrap().to_line(), canonical.to_line());
+        assert_eq!(
+            canonical.canonical().unwrap().to_line(),
+            canonical.to_line()
+        );
+        assert_eq!(
+            canonical.solutions().count(),
+            1,
+            "canonical forms keep solutions"
+        );
+        assert!(canonical.to_line().starts_with('.'));
+    }
+
+    #[test]
+    fn rectangular_boxes() {
+        let pzl = Puzzle::new(Geometry::new(2, 3));
+        let mut one = pzl.clone();
+        one.pin(0, 0, crate::Value::new(4));
+        let line = one.canonical().unwrap().to_line();
+        assert_eq!(line.len(), 36);
+        assert!(line.ends_with("1"));
+        assert!(Puzzle::new(Geometry::new(4, 4)).canonical().is_none());
+        let irregular = Regions::new([[0, 0, 1, 1], [0, 0, 1, 1], [2, 3, 3, 3], [2, 2, 2, 3]]);
+        assert!(Puzzle::new(Geometry::new(2, 2))
+            .with_regions(irregular.unwrap())
+            .canonical()
+            .is_none());
+    }
+}
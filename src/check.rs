@@ -0,0 +1,314 @@
+use std::fmt;
+
+use crate::constraint::Constraint;
+use crate::{Grid, LatinSquare, Puzzle, Value};
+
+/// Group of cells holding distinct values, where a [`Violation`] is found.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Unit {
+    /// A row, 0-indexed.
+    Row(usize),
+    /// A column, 0-indexed.
+    Column(usize),
+    /// A box, or a jigsaw region, 0-indexed.
+    Region(usize),
+    /// A group of another constraint, such as a diagonal.
+    Other,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Column(col) => write!(f, "column {}", col + 1),
+            Unit::Region(region) => write!(f, "region {}", region + 1),
+            Unit::Other => write!(f, "a group of distinct values"),
+        }
+    }
+}
+
+/// Reason why a grid is not a solution of a puzzle, see
+/// [`Puzzle::check`].  Cells are 0-indexed, and displayed 1-indexed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Violation {
+    /// The grid does not have the size of the puzzle, given here.
+    WrongSize(usize),
+    /// A cell is empty, with a value of 0.
+    Empty(usize, usize),
+    /// A cell holds a value larger than the size of the grid.
+    OutOfRange(usize, usize),
+    /// A cell does not hold the value given by the puzzle.
+    ChangedGiven(usize, usize),
+    /// Two cells of a unit hold the same value.
+    Duplicate(Unit, (usize, usize), (usize, usize)),
+    /// The values break another constraint of the puzzle, such as a cage
+    /// sum.
+    Constraint,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::WrongSize(size) => write!(f, "the grid is not {size} by {size}"),
+            Violation::Empty(row, col) => write!(f, "r{}c{} is empty", row + 1, col + 1),
+            Violation::OutOfRange(row, col) => {
+                write!(f, "r{}c{} holds a value out of range", row + 1, col + 1)
+            }
+            Violation::ChangedGiven(row, col) => {
+                write!(f, "r{}c{} does not match its given", row + 1, col + 1)
+            }
+            Violation::Duplicate(unit, (row1, col1), (row2, col2)) => write!(
+                f,
+                "r{}c{} and r{}c{} repeat a value in {unit}",
+                row1 + 1,
+                col1 + 1,
+                row2 + 1,
+                col2 + 1
+            ),
+            Violation::Constraint => write!(f, "the values break a variant constraint"),
+        }
+    }
+}
+
+/// Whether a puzzle has a single solution, with witnesses, see
+/// [`Puzzle::check_uniqueness`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Uniqueness {
+    /// The puzzle has no solution.
+    NoSolution,
+    /// The puzzle has a single solution.
+    Unique(Grid<u8>),
+    /// The puzzle has at least two solutions.
+    Multiple {
+        /// The first solution found.
+        first: Grid<u8>,
+        /// Another solution.
+        second: Grid<u8>,
+        /// The cells where the two solutions differ, in reading order.  Adding
+        /// the value of a solution in any of them as a given rules out the
+        /// other solution.
+        differences: Vec<(usize, usize)>,
+    },
+}
+
+impl Puzzle {
+    /// Check whether the puzzle has a single solution, with two distinct
+    /// solutions and the cells where they differ when it has several.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, Uniqueness};
+    ///
+    /// let pzl = Puzzle::from_line("123434122.4.4.2.").unwrap();
+    /// let Uniqueness::Multiple { first, second, differences } = pzl.check_uniqueness() else {
+    ///     panic!("the puzzle has two solutions");
+    /// };
+    /// assert_eq!(differences, [(2, 1), (2, 3), (3, 1), (3, 3)]);
+    /// assert_ne!(first[2][1], second[2][1]);
+    ///
+    /// let mut pzl = pzl;
+    /// pzl.pin(2, 1, sudoku_solver::Value::new(first[2][1]));
+    /// assert_eq!(pzl.check_uniqueness(), Uniqueness::Unique(first));
+    /// ```
+    pub fn check_uniqueness(&self) -> Uniqueness {
+        let mut sols = self.solutions();
+        let Some(first) = sols.next() else {
+            return Uniqueness::NoSolution;
+        };
+        let Some(second) = sols.next() else {
+            return Uniqueness::Unique(first);
+        };
+        let differences = first
+            .cells()
+            .filter(|&((row, col), &val)| second[row][col] != val)
+            .map(|(pos, _)| pos)
+            .collect();
+        Uniqueness::Multiple {
+            first,
+            second,
+            differences,
+        }
+    }
+
+    /// Check that a full grid is a solution of the puzzle, returning all the
+    /// ways it is not, or nothing for a solution.
+    ///
+    /// Groups of distinct values are checked one by one.  Other constraints
+    /// are only checked as a whole, and only once all the values are in range
+    /// and distinct in their groups.  A grid of the wrong size gives a single
+    /// [`Violation::WrongSize`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, Unit, Violation};
+    ///
+    /// let pzl = Puzzle::from_line("1...............").unwrap();
+    /// let grid = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+    /// assert!(pzl.check(&grid.into()).is_empty());
+    ///
+    /// let grid = [[2, 1, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 0, 1]];
+    /// assert_eq!(
+    ///     pzl.check(&grid.into()),
+    ///     [
+    ///         Violation::Empty(3, 2),
+    ///         Violation::ChangedGiven(0, 0),
+    ///         Violation::Duplicate(Unit::Column(0), (0, 0), (2, 0)),
+    ///         Violation::Duplicate(Unit::Column(1), (0, 1), (2, 1)),
+    ///     ],
+    /// );
+    /// ```
+    pub fn check(&self, grid: &Grid<u8>) -> Vec<Violation> {
+        let size = self.geometry().size();
+        if grid.size() != size {
+            return vec![Violation::WrongSize(size)];
+        }
+        let mut violations = Vec::new();
+        for ((row, col), &val) in grid.cells() {
+            if val == 0 {
+                violations.push(Violation::Empty(row, col));
+            } else if val as usize > size {
+                violations.push(Violation::OutOfRange(row, col));
+            }
+        }
+        for ((row, col), &val) in grid.cells() {
+            if self.get(row, col).is_some_and(|given| given.value() != val) {
+                violations.push(Violation::ChangedGiven(row, col));
+            }
+        }
+        let in_range = violations
+            .iter()
+            .all(|v| matches!(v, Violation::ChangedGiven(..)));
+        let units = (0..size)
+            .map(Unit::Row)
+            .chain((0..size).map(Unit::Column))
+            .zip(LatinSquare.groups(size))
+            .chain(
+                self.regions()
+                    .groups(size)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, group)| (Unit::Region(i), group)),
+            )
+            .chain(
+                self.added_constraints()
+                    .flat_map(|c| c.groups(size))
+                    .map(|group| (Unit::Other, group)),
+            );
+        let mut distinct = true;
+        for (unit, group) in units {
+            let mut seen = vec![None; size + 1];
+            for (row, col) in group {
+                let val = grid[row][col] as usize;
+                if val == 0 || val > size {
+                    continue;
+                }
+                match seen[val] {
+                    Some(first) => {
+                        distinct = false;
+                        violations.push(Violation::Duplicate(unit, first, (row, col)));
+                    }
+                    None => seen[val] = Some((row, col)),
+                }
+            }
+        }
+        if in_range && distinct {
+            let mut filled = self.clone();
+            for ((row, col), &val) in grid.cells() {
+                filled.pin(row, col, Value::new(val));
+            }
+            if filled.solutions().next().is_none() {
+                violations.push(Violation::Constraint);
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Geometry, Thermometers};
+
+    const SOLUTION: [[u8; 4]; 4] = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+    #[test]
+    fn valid_solutions() {
+        let pzl = Puzzle::from_arr([[1, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 3], [0, 0, 0, 0]]);
+        assert!(pzl.check(&SOLUTION.into()).is_empty());
+        let classic = Puzzle::default();
+        let sol = classic.solutions().next().unwrap();
+        assert!(classic.check(&sol).is_empty());
+    }
+
+    #[test]
+    fn uniqueness_witnesses() {
+        let pzl = Puzzle::from_arr(SOLUTION);
+        assert_eq!(pzl.check_uniqueness(), Uniqueness::Unique(SOLUTION.into()));
+        let mut bad = pzl.clone();
+        bad.unpin(0, 0);
+        bad.pin(0, 0, Value::new(2));
+        assert_eq!(bad.check_uniqueness(), Uniqueness::NoSolution);
+        let pzl = Puzzle::new(Geometry::new(2, 2));
+        let Uniqueness::Multiple {
+            first,
+            second,
+            differences,
+        } = pzl.check_uniqueness()
+        else {
+            panic!("the empty puzzle has several solutions");
+        };
+        assert!(pzl.check(&first).is_empty() && pzl.check(&second).is_empty());
+        assert!(!differences.is_empty());
+        for ((row, col), &val) in first.cells() {
+            assert_eq!(differences.contains(&(row, col)), second[row][col] != val);
+        }
+    }
+
+    #[test]
+    fn all_violations_are_listed() {
+        let pzl = Puzzle::from_arr([[1, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]]);
+        let grid = [[1, 2, 3, 4], [3, 4, 0, 2], [2, 1, 4, 3], [4, 3, 2, 9]];
+        assert_eq!(
+            pzl.check(&grid.into()),
+            [Violation::Empty(1, 2), Violation::OutOfRange(3, 3)]
+        );
+        // A latin square, with two repeated values in each box.
+        let grid = [[1, 2, 3, 4], [2, 1, 4, 3], [3, 4, 1, 2], [4, 3, 2, 1]];
+        let violations = pzl.check(&grid.into());
+        assert_eq!(violations.len(), 8);
+        assert_eq!(
+            violations[..2],
+            [
+                Violation::Duplicate(Unit::Region(0), (0, 1), (1, 0)),
+                Violation::Duplicate(Unit::Region(0), (0, 0), (1, 1)),
+            ]
+        );
+        let grid = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 1, 2]];
+        assert_eq!(
+            pzl.check(&grid.into()),
+            [
+                Violation::Duplicate(Unit::Column(2), (1, 2), (3, 2)),
+                Violation::Duplicate(Unit::Column(3), (1, 3), (3, 3)),
+            ]
+        );
+        assert_eq!(
+            Puzzle::default().check(&SOLUTION.into()),
+            [Violation::WrongSize(9)]
+        );
+    }
+
+    #[test]
+    fn variant_constraints() {
+        let thermo = Thermometers::new(4, vec![vec![(0, 1), (0, 0)]]).unwrap();
+        let pzl = Puzzle::new(Geometry::new(2, 2)).with_constraint(thermo);
+        assert_eq!(pzl.check(&SOLUTION.into()), [Violation::Constraint]);
+        let mirrored = Grid::from_fn(4, |row, col| SOLUTION[row][3 - col]);
+        assert!(pzl.check(&mirrored).is_empty());
+        assert_eq!(
+            Violation::Duplicate(Unit::Row(0), (0, 0), (0, 3)).to_string(),
+            "r1c1 and r1c4 repeat a value in row 1"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::any::Any;
+use std::fmt;
+
+use crate::puzzle::Value;
+use crate::solgrid::{NoSolError, SolResult, SolutionGrid};
+
+/// A rule that solutions of a [`Puzzle`] must follow.
+///
+/// Rules are described in two complementary ways, both optional:
+///
+/// - [`Constraint::groups`] lists sets of cells that must hold distinct
+///   values.  The solver removes a value from the candidates of all the other
+///   cells of a group as soon as it is placed in one of them, and places a
+///   value in the only cell of a group of full size that can still hold it.
+/// - [`Constraint::propagate`] is called whenever the solver runs out of such
+///   deductions, and removes candidates that break the rule from the
+///   [`GridState`] it is given.
+///
+/// The classic rules are [`LatinSquare`] (rows and columns) and the
+/// [`Regions`] of the puzzle, other constraints are added with
+/// [`Puzzle::with_constraint`].
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Constraint, Contradiction, GridState, Puzzle, Value};
+///
+/// /// The top-left cell holds an even value.
+/// struct EvenCorner;
+///
+/// impl Constraint for EvenCorner {
+///     fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+///         for val in (1..=grid.size() as u8).step_by(2) {
+///             grid.forbid(0, 0, Value::new(val))?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let pzl = Puzzle::default().with_constraint(EvenCorner);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_eq!(sol[0][0], 2);
+/// ```
+///
+/// [`Puzzle`]: crate::Puzzle
+/// [`Puzzle::with_constraint`]: crate::Puzzle::with_constraint
+/// [`Regions`]: crate::Regions
+pub trait Constraint: Any + Send + Sync {
+    /// The size of the only grids the rule applies to, if it is made for a
+    /// given size, e.g. because it refers to cells by their position.  This is
+    /// checked by [`Puzzle::with_constraint`].
+    ///
+    /// [`Puzzle::with_constraint`]: crate::Puzzle::with_constraint
+    fn size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Sets of cells that must hold distinct values in a grid of a given
+    /// size, as `(row, col)` positions.
+    fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        let _ = size;
+        Vec::new()
+    }
+
+    /// Remove candidates that break the rule.  This must return a
+    /// [`Contradiction`] at the latest once all the cells are pinned to values
+    /// that break the rule, and may do so earlier.
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        let _ = grid;
+        Ok(())
+    }
+}
+
+/// Error signaling that the state of a grid breaks a [`Constraint`], i.e. the
+/// puzzle has no solution from that state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction;
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the grid breaks a constraint")
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+impl From<NoSolError> for Contradiction {
+    fn from(_: NoSolError) -> Self {
+        Contradiction
+    }
+}
+
+impl From<Contradiction> for NoSolError {
+    fn from(_: Contradiction) -> Self {
+        NoSolError {}
+    }
+}
+
+/// Candidates of each cell during the search, as seen by
+/// [`Constraint::propagate`].  Candidates are given as bitmasks, where the bit
+/// `1 << (v - 1)` is set if the value `v` is a candidate.  Positions are
+/// 0-indexed.
+pub struct GridState<'a> {
+    grid: &'a mut SolutionGrid,
+    changed: bool,
+}
+
+impl<'a> GridState<'a> {
+    pub(crate) fn new(grid: &'a mut SolutionGrid) -> Self {
+        Self {
+            grid,
+            changed: false,
+        }
+    }
+
+    /// Whether candidates have been removed through this state.
+    pub(crate) fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Number of rows, columns, and values of the grid.
+    pub fn size(&self) -> usize {
+        self.grid.size()
+    }
+
+    /// The value of a cell, if it is known for sure.
+    pub fn value(&self, row: usize, col: usize) -> Option<Value> {
+        self.grid.pinned(row, col)
+    }
+
+    /// Bitmask of the candidates of a cell.
+    pub fn candidates(&self, row: usize, col: usize) -> u32 {
+        self.grid.candidates(row, col).bits()
+    }
+
+    /// Whether a value is a candidate of a cell.
+    pub fn is_possible(&self, row: usize, col: usize, val: Value) -> bool {
+        self.grid.candidates(row, col).has_solution(val)
+    }
+
+    /// Remove a value from the candidates of a cell.  This is a
+    /// [`Contradiction`] if the cell is left without candidates.
+    pub fn forbid(&mut self, row: usize, col: usize, val: Value) -> Result<(), Contradiction> {
+        if self.is_possible(row, col, val) {
+            self.changed = true;
+            self.grid.forbid(row, col, val)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the candidates of a cell that are not in the bitmask `allowed`.
+    /// This is a [`Contradiction`] if the cell is left without candidates.
+    pub fn restrict(&mut self, row: usize, col: usize, allowed: u32) -> Result<(), Contradiction> {
+        let mut forbidden = self.candidates(row, col) & !allowed;
+        while forbidden != 0 {
+            self.forbid(row, col, Value::new(forbidden.trailing_zeros() as u8 + 1))?;
+            forbidden &= forbidden - 1;
+        }
+        Ok(())
+    }
+}
+
+/// Run [`Constraint::propagate`] for all the constraints of a grid, returning
+/// whether candidates were removed.
+pub(crate) fn propagate_all(grid: &mut SolutionGrid) -> SolResult<bool> {
+    let layout = grid.layout().clone();
+    let mut state = GridState::new(grid);
+    for constraint in layout.constraints() {
+        constraint.propagate(&mut state)?;
+    }
+    Ok(state.changed())
+}
+
+/// The rows and columns of a grid must hold distinct values.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct LatinSquare;
+
+impl Constraint for LatinSquare {
+    fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        let rows = (0..size).map(|row| (0..size).map(|col| (row, col)).collect());
+        let cols = (0..size).map(|col| (0..size).map(|row| (row, col)).collect());
+        rows.chain(cols).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    use crate::{CandidateSet, Geometry, Puzzle};
+
+    /// The top-left cell is smaller than its right neighbour, only checked
+    /// once both are pinned.
+    struct LazyLess;
+
+    impl Constraint for LazyLess {
+        fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+            match (grid.value(0, 0), grid.value(0, 1)) {
+                (Some(a), Some(b)) if a >= b => Err(Contradiction),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Same rule as [`LazyLess`], removing candidates eagerly.
+    struct EagerLess;
+
+    impl Constraint for EagerLess {
+        fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+            let left = grid.candidates(0, 0);
+            let right = grid.candidates(0, 1);
+            // Values above the smallest candidate on the left.
+            grid.restrict(0, 1, !((left & left.wrapping_neg()) * 2 - 1))?;
+            // Values below the largest candidate on the right.
+            grid.restrict(0, 0, (1 << (31 - right.leading_zeros())) - 1)
+        }
+    }
+
+    /// The main diagonal holds distinct values.
+    struct Diagonal;
+
+    impl Constraint for Diagonal {
+        fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+            vec![(0..size).map(|i| (i, i)).collect()]
+        }
+    }
+
+    fn small_puzzle() -> Puzzle {
+        Puzzle::new(Geometry::new(2, 2))
+    }
+
+    #[test]
+    fn propagation_prunes_solutions() {
+        assert_eq!(small_puzzle().solutions().count(), 288);
+        for pzl in [
+            small_puzzle().with_constraint(LazyLess),
+            small_puzzle().with_constraint(EagerLess),
+        ] {
+            let sols: Vec<_> = pzl.solutions().collect();
+            assert_eq!(sols.len(), 144);
+            assert!(sols.iter().all(|sol| sol[0][0] < sol[0][1]));
+            let pvals = pzl.possible_values();
+            assert_eq!(pvals[0][0], CandidateSet::from([1, 2, 3]));
+            assert_eq!(pvals[0][1], CandidateSet::from([2, 3, 4]));
+        }
+    }
+
+    #[test]
+    fn groups_are_distinct() {
+        let pzl = small_puzzle().with_constraint(Diagonal);
+        let sols: Vec<_> = pzl.solutions().collect();
+        assert!(!sols.is_empty() && sols.len() < 288);
+        for sol in sols {
+            let diag: BTreeSet<_> = (0..4).map(|i| sol[i][i]).collect();
+            assert_eq!(diag.len(), 4);
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use crate::soft::SoftConstraint;
+use crate::solgrid::{Layout, SolutionGrid};
+use crate::solver::SearchOutcome;
+use crate::{CandidateGrid, CandidateSet, Edit, Grid, Puzzle, Value};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+
+/// A helper to find all the values in each cell that lead to a
+/// solvable [`Puzzle`].
+pub struct PossibleValuesFinder {
+    keep_going: Arc<AtomicBool>,
+    budget: Option<u64>,
+    n_threads: usize,
+}
+
+impl PossibleValuesFinder {
+    pub fn new() -> Self {
+        Self::with_handle(Arc::new(true.into()))
+    }
+
+    /// Set the `Arc<AtomicBool>` to use as a "keep going" flag. Similarly to
+    /// [`SolutionIterator::with_handle`], this is useful to have a way to stop
+    /// a separate thread running [`Self::search`].
+    ///
+    /// [`SolutionIterator::with_handle`]: crate::SolutionIterator::with_handle
+    pub fn with_handle(keep_going: Arc<AtomicBool>) -> Self {
+        Self {
+            keep_going,
+            budget: None,
+            n_threads: 1,
+        }
+    }
+
+    /// Limit the total number of search tree nodes explored by
+    /// [`Self::search`], see [`SolutionIterator::with_budget`].
+    ///
+    /// [`SolutionIterator::with_budget`]: crate::SolutionIterator::with_budget
+    pub fn with_budget(self, max_nodes: u64) -> Self {
+        Self {
+            budget: Some(max_nodes),
+            ..self
+        }
+    }
+
+    /// Set the number of threads used by [`Self::search`], the calling thread
+    /// included.  The nodes of the search tree are then distributed over that
+    /// many threads, all sharing the set of values already found in a
+    /// solution.  The "keep going" flag set with
+    /// [`Self::with_handle`] stops all of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_threads` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::thread;
+    /// use sudoku_solver::{PossibleValuesFinder, Puzzle, Value};
+    ///
+    /// let mut pzl = Puzzle::default();
+    /// pzl.pin(4, 4, Value::new(5));
+    /// let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    /// let pvs = PossibleValuesFinder::new()
+    ///     .with_threads(n_threads)
+    ///     .search(&pzl)
+    ///     .completed()
+    ///     .unwrap();
+    /// assert_eq!(pvs, pzl.possible_values());
+    /// ```
+    pub fn with_threads(self, n_threads: usize) -> Self {
+        assert!(n_threads > 0, "At least one thread is needed.");
+        Self { n_threads, ..self }
+    }
+
+    /// Search all the values in the non-pinned cells of a given [`Puzzle`]
+    /// that lead to a solvable [`Puzzle`].
+    ///
+    /// A single search tree is explored, and every value seen in a solution
+    /// found along the way is marked as possible right away.  Branches whose
+    /// remaining candidates are all marked are pruned, and branching favours
+    /// values that have not been marked yet so that each solution found
+    /// marks as many values as possible.
+    ///
+    /// The result is only meaningful if the search ran to completion: a
+    /// cancelled search is reported as [`SearchOutcome::Cancelled`] rather
+    /// than as a grid without possible values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{atomic::AtomicBool, Arc};
+    /// use sudoku_solver::{PossibleValuesFinder, Puzzle, SearchOutcome};
+    ///
+    /// let stopped = Arc::new(AtomicBool::new(false));
+    /// let outcome = PossibleValuesFinder::with_handle(stopped).search(&Puzzle::default());
+    /// assert_eq!(outcome, SearchOutcome::Cancelled);
+    /// ```
+    pub fn search(self, puzzle: &Puzzle) -> SearchOutcome<Grid<CandidateSet>> {
+        let confirmed = Grid::filled(puzzle.geometry().size(), SoftConstraint::empty());
+        let outcome = match puzzle.try_into() {
+            Ok(root) => self.explore(root, confirmed),
+            Err(_) => SearchOutcome::Completed(confirmed),
+        };
+        outcome.map(to_sets)
+    }
+
+    /// Search the candidates of a [`CandidateGrid`] that lead to a solution,
+    /// like [`Self::search`].  Other values are never possible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{CandidateGrid, PossibleValuesFinder, Puzzle, Value};
+    ///
+    /// let mut cands = CandidateGrid::new(&Puzzle::default());
+    /// cands.remove(0, 0, Value::new(1));
+    /// let pvs = PossibleValuesFinder::new()
+    ///     .search_candidates(&cands)
+    ///     .completed()
+    ///     .unwrap();
+    /// assert_eq!(pvs[0][0].len(), 8);
+    /// assert_eq!(pvs[0][1].len(), 9);
+    /// ```
+    pub fn search_candidates(
+        self,
+        candidates: &CandidateGrid,
+    ) -> SearchOutcome<Grid<CandidateSet>> {
+        let puzzle = candidates.puzzle();
+        let confirmed = Grid::filled(puzzle.geometry().size(), SoftConstraint::empty());
+        let outcome = match candidates.root(Arc::new(Layout::new(puzzle))) {
+            Some(root) => self.explore(root, confirmed),
+            None => SearchOutcome::Completed(confirmed),
+        };
+        outcome.map(to_sets)
+    }
+
+    /// Search the possible values of a [`Puzzle`] after a single [`Edit`],
+    /// reusing the possible values `previous` of the puzzle before the edit.
+    ///
+    /// Pinning a cell only removes solutions: the search is restricted to the
+    /// previous possible values, and is skipped altogether if the pinned
+    /// value was the only possible one.  Unpinning a cell only adds
+    /// solutions: the previous possible values are known to remain possible
+    /// and only the other values are searched for.
+    ///
+    /// `previous` must be the possible values of `puzzle`, as returned by a
+    /// completed [`Self::search`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Edit, PossibleValuesFinder, Puzzle, Value};
+    ///
+    /// let mut pzl = Puzzle::default();
+    /// pzl.pin(0, 0, Value::new(1));
+    /// let pvs = pzl.possible_values();
+    ///
+    /// let edit = Edit::Pin(4, 4, Value::new(5));
+    /// let updated = PossibleValuesFinder::new()
+    ///     .search_after_edit(&pzl, &pvs, edit)
+    ///     .completed()
+    ///     .unwrap();
+    ///
+    /// pzl.apply(edit);
+    /// assert_eq!(updated, pzl.possible_values());
+    /// ```
+    pub fn search_after_edit(
+        self,
+        puzzle: &Puzzle,
+        previous: &Grid<CandidateSet>,
+        edit: Edit,
+    ) -> SearchOutcome<Grid<CandidateSet>> {
+        let mut edited = puzzle.clone();
+        edited.apply(edit);
+        let previous = previous.clone().map(SoftConstraint::from);
+        let (root, confirmed) = match edit {
+            Edit::Pin(row, col, val) if puzzle.get(row, col).is_none() => {
+                if previous[row][col].unique_solution() == Some(val) {
+                    // The set of solutions is unchanged.
+                    return SearchOutcome::Completed(to_sets(previous));
+                }
+                let root = SolutionGrid::try_from(&edited)
+                    .and_then(|mut root| root.restrict(&previous).map(|()| root));
+                let size = puzzle.geometry().size();
+                (root, Grid::filled(size, SoftConstraint::empty()))
+            }
+            Edit::Unpin(row, col) if puzzle.get(row, col).is_some() => {
+                (SolutionGrid::try_from(&edited), previous)
+            }
+            // Changing a pinned value does not preserve solutions, and
+            // no-op edits are cheap to handle as a fresh search.
+            _ => return self.search(&edited),
+        };
+        match root {
+            Ok(root) => self.explore(root, confirmed).map(to_sets),
+            Err(_) => SearchOutcome::Completed(to_sets(confirmed)),
+        }
+    }
+
+    /// Add the values of solutions of `root` to `confirmed` until every
+    /// candidate of `root` is either confirmed or refuted.
+    fn explore(
+        &self,
+        root: SolutionGrid,
+        confirmed: Grid<SoftConstraint>,
+    ) -> SearchOutcome<Grid<SoftConstraint>> {
+        let shared = SharedSearch::new(root, confirmed);
+        let outcome = thread::scope(|s| {
+            let workers: Vec<_> = (1..self.n_threads)
+                .map(|_| s.spawn(|| self.work(&shared)))
+                .collect();
+            let outcome = self.work(&shared);
+            workers
+                .into_iter()
+                .map(|w| w.join().unwrap())
+                .fold(outcome, |acc, o| match (acc, o) {
+                    (SearchOutcome::Completed(()), o) => o,
+                    (acc, _) => acc,
+                })
+        });
+        outcome.map(|()| shared.confirmed())
+    }
+
+    /// Explore nodes of the shared search tree until none is left, or the
+    /// search is stopped.
+    fn work(&self, shared: &SharedSearch) -> SearchOutcome<()> {
+        while let Some((node, _active)) = shared.pop() {
+            if !self.keep_going.load(Ordering::Relaxed) {
+                shared.stop();
+                return SearchOutcome::Cancelled;
+            }
+            let explored = shared.explored.fetch_add(1, Ordering::Relaxed);
+            if self.budget.is_some_and(|b| explored >= b) {
+                shared.stop();
+                return SearchOutcome::BudgetExceeded;
+            }
+            let children = match node.into_grid(shared) {
+                Some(grid) => self.branch(grid, shared),
+                None => Vec::new(),
+            };
+            shared.push(children);
+        }
+        SearchOutcome::Completed(())
+    }
+
+    /// The children of a node of the search tree, none if it is solved or
+    /// if all its candidates are already confirmed.
+    ///
+    /// Branching is on a cell with unconfirmed candidates, if any, trying an
+    /// unconfirmed value first, so that each solution found confirms as many
+    /// values as possible and each branch settles a candidate.
+    fn branch(&self, grid: SolutionGrid, shared: &SharedSearch) -> Vec<Node> {
+        if grid.is_solved() {
+            shared.confirm(&grid);
+            return Vec::new();
+        }
+        let size = grid.size();
+        let mut pinned_unconfirmed = false;
+        let mut best: Option<(usize, usize, SoftConstraint)> = None;
+        for row in 0..size {
+            for col in 0..size {
+                let candidates = grid.candidates(row, col);
+                let unconfirmed = candidates.difference(shared.confirmed_at(row, col));
+                if !unconfirmed.has_solutions() {
+                    continue;
+                }
+                // Propagation pins the cells left with a single candidate.
+                if candidates.num_solutions() == 1 {
+                    pinned_unconfirmed = true;
+                } else if best.map_or(u32::MAX, |(_, _, b)| b.num_solutions())
+                    > candidates.num_solutions()
+                {
+                    best = Some((row, col, candidates));
+                }
+            }
+        }
+        let (row, col, val) = match best {
+            Some((row, col, candidates)) => {
+                let unconfirmed = candidates.difference(shared.confirmed_at(row, col));
+                (row, col, unconfirmed.smallest_solution())
+            }
+            // Only the values of pinned cells are left to confirm, by any
+            // solution below this node.
+            None if pinned_unconfirmed => match grid.find_least_sols_fuzzy() {
+                Some((row, col, sc)) => (row, col, sc.smallest_solution()),
+                None => return Vec::new(),
+            },
+            // Nothing new can be found below this node.
+            None => return Vec::new(),
+        };
+        let Some(val) = val else {
+            return Vec::new();
+        };
+        let mut children = Vec::with_capacity(2);
+        let mut pinned = grid.clone();
+        children.push(Node {
+            grid,
+            forbid: Some((row, col, val)),
+        });
+        if pinned.pin(row, col, val).is_ok() && pinned.maximize_constraints().is_ok() {
+            children.push(Node {
+                grid: pinned,
+                forbid: None,
+            });
+        }
+        children
+    }
+}
+
+impl Default for PossibleValuesFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert bitmasks of values to sets of values.
+fn to_sets(grid: Grid<SoftConstraint>) -> Grid<CandidateSet> {
+    grid.map(CandidateSet::from)
+}
+
+/// State shared by the threads running a [`PossibleValuesFinder`] search.
+struct SharedSearch {
+    /// Nodes of the search tree left to explore.
+    pending: Mutex<Pending>,
+    /// Signals changes of `pending` to the threads waiting for nodes.
+    changed: Condvar,
+    /// Bitmasks of the values found in a solution, see [`SoftConstraint`].
+    confirmed: Grid<AtomicU32>,
+    /// Number of search tree nodes explored by all threads.
+    explored: AtomicU64,
+}
+
+/// Nodes of the search tree left to explore.
+struct Pending {
+    /// Siblings of the nodes on the paths being explored, shallowest first.
+    nodes: VecDeque<Node>,
+    /// Number of nodes being explored, whose children are yet to be pushed.
+    active: usize,
+    /// Whether a solution confirmed new values since the last node was
+    /// taken.
+    found: bool,
+    /// Set when a thread stops early, so that the others stop too.
+    stopped: bool,
+}
+
+/// A node of the search tree.  The value to forbid is only forbidden when
+/// the node is explored, so that propagation is skipped for the nodes whose
+/// candidates all got confirmed in the meantime.
+struct Node {
+    grid: SolutionGrid,
+    forbid: Option<(usize, usize, Value)>,
+}
+
+impl Node {
+    /// The grid of this node, unless it has no solution or nothing new can
+    /// be found below it.
+    fn into_grid(self, shared: &SharedSearch) -> Option<SolutionGrid> {
+        let Some((row, col, val)) = self.forbid else {
+            return Some(self.grid);
+        };
+        if !shared.has_unconfirmed(&self.grid) {
+            return None;
+        }
+        let mut grid = self.grid;
+        grid.forbid(row, col, val).ok()?;
+        grid.maximize_constraints().ok()?;
+        Some(grid)
+    }
+}
+
+/// Marks a node taken with [`SharedSearch::pop`] as being explored until it
+/// is dropped, even if the thread exploring it panics.
+struct Active<'a>(&'a SharedSearch);
+
+impl Drop for Active<'_> {
+    fn drop(&mut self) {
+        let mut pending = self
+            .0
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        pending.active -= 1;
+        // A panic stops the search rather than leave the tree incomplete.
+        pending.stopped |= thread::panicking();
+        self.0.changed.notify_all();
+    }
+}
+
+impl SharedSearch {
+    fn new(root: SolutionGrid, confirmed: Grid<SoftConstraint>) -> Self {
+        Self {
+            pending: Mutex::new(Pending {
+                nodes: VecDeque::from([Node {
+                    grid: root,
+                    forbid: None,
+                }]),
+                active: 0,
+                found: false,
+                stopped: false,
+            }),
+            changed: Condvar::new(),
+            confirmed: confirmed.map(|sc| AtomicU32::new(sc.bits())),
+            explored: AtomicU64::new(0),
+        }
+    }
+
+    /// Take the deepest node left, or the shallowest one right after a
+    /// solution confirmed new values: the nodes left close to that solution
+    /// are unlikely to hold other new values.  If there is none, wait for the
+    /// nodes being explored.  This is `None` once the whole tree has been
+    /// explored or the search is stopped.
+    fn pop(&self) -> Option<(Node, Active<'_>)> {
+        let mut pending = self.pending.lock().unwrap();
+        loop {
+            if pending.stopped {
+                return None;
+            }
+            let node = if std::mem::take(&mut pending.found) {
+                pending.nodes.pop_front()
+            } else {
+                pending.nodes.pop_back()
+            };
+            if let Some(node) = node {
+                pending.active += 1;
+                return Some((node, Active(self)));
+            }
+            if pending.active == 0 {
+                return None;
+            }
+            pending = self.changed.wait(pending).unwrap();
+        }
+    }
+
+    /// Add the children of a node taken with [`Self::pop`], the last one to
+    /// be explored first.
+    fn push(&self, children: Vec<Node>) {
+        if !children.is_empty() {
+            self.pending.lock().unwrap().nodes.extend(children);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Stop the search in all threads.
+    fn stop(&self) {
+        self.pending.lock().unwrap().stopped = true;
+        self.changed.notify_all();
+    }
+
+    fn confirmed_at(&self, row: usize, col: usize) -> SoftConstraint {
+        SoftConstraint::from_bits(self.confirmed[row][col].load(Ordering::Relaxed)).unwrap()
+    }
+
+    /// Whether some candidate of `grid` is not confirmed yet.
+    fn has_unconfirmed(&self, grid: &SolutionGrid) -> bool {
+        self.confirmed.cells().any(|((row, col), conf)| {
+            let bits = grid.candidates(row, col).bits();
+            conf.load(Ordering::Relaxed) & bits != bits
+        })
+    }
+
+    /// Mark all the values of a solved grid as confirmed.
+    fn confirm(&self, grid: &SolutionGrid) {
+        let mut new = false;
+        for ((row, col), conf) in self.confirmed.cells() {
+            let bits = grid.candidates(row, col).bits();
+            new |= conf.fetch_or(bits, Ordering::Relaxed) & bits != bits;
+        }
+        if new {
+            self.pending.lock().unwrap().found = true;
+        }
+    }
+
+    fn confirmed(&self) -> Grid<SoftConstraint> {
+        Grid::from_fn(self.confirmed.size(), |row, col| {
+            self.confirmed_at(row, col)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::triple_sol_puzzle;
+    use crate::{Constraint, Contradiction, GridState};
+
+    /// Union of the values of all solutions of a puzzle.
+    fn values_of_all_solutions(puzzle: &Puzzle) -> Grid<CandidateSet> {
+        let mut pvs: Grid<CandidateSet> = Grid::default();
+        for sol in puzzle.solutions() {
+            for (row, sol_row) in sol.iter().enumerate() {
+                for (col, &v) in sol_row.iter().enumerate() {
+                    pvs[row][col].insert(v);
+                }
+            }
+        }
+        pvs
+    }
+
+    #[test]
+    fn possible_values_match_solutions() {
+        let pzl = triple_sol_puzzle();
+        assert_eq!(pzl.possible_values(), values_of_all_solutions(&pzl));
+    }
+
+    #[test]
+    fn possible_values_no_solution() {
+        let mut pzl = triple_sol_puzzle();
+        pzl.pin(0, 1, Value::new(3));
+        assert_eq!(pzl.possible_values(), Grid::<CandidateSet>::default());
+    }
+
+    #[test]
+    fn possible_values_budget_exceeded() {
+        let outcome = PossibleValuesFinder::new()
+            .with_budget(5)
+            .search(&Puzzle::default());
+        assert_eq!(outcome, SearchOutcome::BudgetExceeded);
+    }
+
+    #[test]
+    fn search_after_edit_matches_search() {
+        let mut pzl = triple_sol_puzzle();
+        pzl.unpin(0, 0);
+        let edits = [
+            Edit::Unpin(0, 2),
+            Edit::Pin(1, 0, Value::new(2)),
+            Edit::Pin(2, 0, Value::new(2)),
+            Edit::Unpin(1, 0),
+            Edit::Pin(0, 0, Value::new(3)),
+            Edit::Pin(0, 0, Value::new(5)),
+            Edit::Unpin(0, 0),
+        ];
+        let mut pvs = pzl.possible_values();
+        for edit in edits {
+            pvs = PossibleValuesFinder::new()
+                .search_after_edit(&pzl, &pvs, edit)
+                .completed()
+                .unwrap();
+            pzl.apply(edit);
+            assert_eq!(pvs, values_of_all_solutions(&pzl), "after {edit:?}");
+        }
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut pzl = triple_sol_puzzle();
+        for col in [0, 2, 3, 6] {
+            pzl.unpin(0, col);
+        }
+        let pvs = PossibleValuesFinder::new()
+            .with_threads(4)
+            .search(&pzl)
+            .completed()
+            .unwrap();
+        assert_eq!(pvs, values_of_all_solutions(&pzl));
+    }
+
+    #[test]
+    fn parallel_cancelled() {
+        let keep_going = Arc::new(AtomicBool::new(false));
+        let outcome = PossibleValuesFinder::with_handle(keep_going)
+            .with_threads(4)
+            .search(&Puzzle::default());
+        assert_eq!(outcome, SearchOutcome::Cancelled);
+    }
+
+    /// Panics once it has been asked to propagate a few times.
+    struct PanickingConstraint(AtomicU64);
+
+    impl Constraint for PanickingConstraint {
+        fn propagate(&self, _: &mut GridState) -> Result<(), Contradiction> {
+            if self.0.fetch_add(1, Ordering::Relaxed) == 20 {
+                panic!("constraint failure");
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn parallel_propagates_panics() {
+        let pzl = Puzzle::default().with_constraint(PanickingConstraint(AtomicU64::new(0)));
+        PossibleValuesFinder::new().with_threads(4).search(&pzl);
+    }
+}
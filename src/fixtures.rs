@@ -0,0 +1,31 @@
+//! Puzzles shared by the unit tests.
+
+use crate::Puzzle;
+
+/// A classic puzzle with a single solution, [`CLASSIC_SOLUTION`].
+pub const CLASSIC_PUZZLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+/// The solution of [`CLASSIC_PUZZLE`].
+pub const CLASSIC_SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// [`CLASSIC_PUZZLE`] as a [`Puzzle`].
+pub fn classic_puzzle() -> Puzzle {
+    Puzzle::from_line(CLASSIC_PUZZLE).unwrap()
+}
+
+/// A classic puzzle with exactly three solutions.
+pub fn triple_sol_puzzle() -> Puzzle {
+    Puzzle::from_arr([
+        [3, 0, 9, 6, 0, 0, 4, 0, 0],
+        [0, 0, 0, 7, 0, 9, 0, 0, 0],
+        [0, 8, 7, 0, 0, 0, 0, 0, 0],
+        [7, 5, 0, 0, 6, 0, 2, 3, 0],
+        [6, 0, 0, 9, 0, 4, 0, 0, 8],
+        [0, 2, 8, 0, 5, 0, 0, 4, 1],
+        [0, 0, 0, 0, 0, 0, 5, 9, 0],
+        [0, 0, 0, 1, 9, 6, 0, 0, 7],
+        [0, 0, 6, 0, 0, 0, 1, 0, 4],
+    ])
+}
@@ -0,0 +1,641 @@
+//! Text format of puzzles, described in the crate documentation.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::{Geometry, Grid, MAX_SIZE};
+use crate::puzzle::{Puzzle, Value};
+use crate::regions::{RegionError, Regions};
+use crate::variants::{
+    AntiKing, AntiKnight, Arrows, Cage, CageError, Diagonals, Dot, Dots, GreaterThan, Killer,
+    Marker, Parity, Sandwich, Sandwiches, ShapeError, Thermometers, Whispers,
+};
+
+/// Error signaling that a text is not a valid description of a [`Puzzle`].
+/// Lines are 1-indexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The text has no `grid` section.
+    MissingGrid,
+    /// The block of rows starting at a given line is not a valid grid.
+    BadGrid(usize),
+    /// The directive at a given line is not known.
+    UnknownDirective(usize),
+    /// The arguments of the directive at a given line are not valid.
+    BadArguments(usize),
+    /// The regions do not partition the grid.
+    Regions(RegionError),
+    /// The cages do not fit in the grid.
+    Cages(CageError),
+    /// The cells of other variant constraints do not fit in the grid.
+    Shapes(ShapeError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingGrid => write!(f, "missing grid"),
+            ParseError::BadGrid(line) => write!(f, "line {line}: invalid grid"),
+            ParseError::UnknownDirective(line) => write!(f, "line {line}: unknown directive"),
+            ParseError::BadArguments(line) => write!(f, "line {line}: invalid arguments"),
+            ParseError::Regions(err) => write!(f, "invalid regions: {err}"),
+            ParseError::Cages(err) => write!(f, "invalid cages: {err}"),
+            ParseError::Shapes(err) => write!(f, "invalid shapes: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Regions(err) => Some(err),
+            ParseError::Cages(err) => Some(err),
+            ParseError::Shapes(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Symbol of a value, digits then letters from `A` for 10.
+fn value_symbol(val: u8) -> char {
+    char::from_digit(val as u32, 36)
+        .unwrap()
+        .to_ascii_uppercase()
+}
+
+/// Value of a symbol written by [`value_symbol`], `Some(None)` for an empty
+/// cell.
+fn symbol_value(symbol: char) -> Option<Option<u8>> {
+    match symbol {
+        '.' | '0' => Some(None),
+        _ => symbol.to_digit(36).map(|v| Some(v as u8)),
+    }
+}
+
+/// Position written as `r<row>c<col>`, 1-indexed.
+fn parse_cell(word: &str) -> Option<(usize, usize)> {
+    let (row, col) = word.strip_prefix('r')?.split_once('c')?;
+    let (row, col): (usize, usize) = (row.parse().ok()?, col.parse().ok()?);
+    Some((row.checked_sub(1)?, col.checked_sub(1)?))
+}
+
+/// Directive of a marker between two cells.
+fn marker_name(marker: Marker) -> &'static str {
+    match marker {
+        Marker::White => "white",
+        Marker::Black => "black",
+        Marker::X => "x",
+        Marker::V => "v",
+    }
+}
+
+fn parse_marker(word: &str) -> Option<Marker> {
+    [Marker::White, Marker::Black, Marker::X, Marker::V]
+        .into_iter()
+        .find(|&marker| marker_name(marker) == word)
+}
+
+fn write_cells(f: &mut fmt::Formatter<'_>, cells: &[(usize, usize)]) -> fmt::Result {
+    for (row, col) in cells {
+        write!(f, " r{}c{}", row + 1, col + 1)?;
+    }
+    Ok(())
+}
+
+/// Lines of a text with their 1-indexed number, without comments and blank
+/// lines.
+struct Lines<'a> {
+    inner: std::iter::Enumerate<std::str::Lines<'a>>,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.find_map(|(i, line)| {
+            let line = line.split('#').next().unwrap().trim();
+            (!line.is_empty()).then_some((i + 1, line))
+        })
+    }
+}
+
+impl Lines<'_> {
+    /// Read a square block of symbols following the header at a given line,
+    /// its size being the number of symbols on the first row, at most
+    /// [`MAX_SIZE`].
+    fn block(&mut self, header: usize) -> Result<Grid<char>, ParseError> {
+        let symbols =
+            |line: &str| -> Vec<char> { line.chars().filter(|c| !c.is_whitespace()).collect() };
+        let (_, first) = self.next().ok_or(ParseError::BadGrid(header))?;
+        let mut cells = symbols(first);
+        let size = cells.len();
+        if size > MAX_SIZE {
+            return Err(ParseError::BadGrid(header));
+        }
+        for _ in 1..size {
+            let (_, line) = self.next().ok_or(ParseError::BadGrid(header))?;
+            let row = symbols(line);
+            if row.len() != size {
+                return Err(ParseError::BadGrid(header));
+            }
+            cells.extend(row);
+        }
+        let mut cells = cells.into_iter();
+        Ok(Grid::from_fn(size, |_, _| cells.next().unwrap()))
+    }
+}
+
+impl Puzzle {
+    /// Read the givens of a puzzle written on a single line, as returned by
+    /// [`Self::to_line`]: one symbol per cell in reading order, `.` or `0`
+    /// for empty cells.  The size of the grid follows from the length of the
+    /// line, and its boxes are the conventional ones for that size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::Puzzle;
+    ///
+    /// let line = "1...\
+    ///             ..3.\
+    ///             ....\
+    ///             ...4";
+    /// let pzl = Puzzle::from_line(line).unwrap();
+    /// assert_eq!(pzl.geometry().size(), 4);
+    /// assert_eq!(pzl.to_line(), "1.....3........4");
+    /// ```
+    pub fn from_line(line: &str) -> Result<Self, ParseError> {
+        let symbols: Vec<char> = line.trim().chars().collect();
+        let size = (1..=MAX_SIZE)
+            .find(|size| size * size == symbols.len())
+            .ok_or(ParseError::BadGrid(1))?;
+        let mut puzzle = Puzzle::new(Geometry::for_size(size).unwrap());
+        for (i, &symbol) in symbols.iter().enumerate() {
+            match symbol_value(symbol).ok_or(ParseError::BadGrid(1))? {
+                Some(val) if val as usize <= size => {
+                    puzzle.pin(i / size, i % size, Value::new(val))
+                }
+                Some(_) => return Err(ParseError::BadGrid(1)),
+                None => {}
+            }
+        }
+        Ok(puzzle)
+    }
+
+    /// Write the givens of the puzzle on a single line, see
+    /// [`Self::from_line`].  Regions and variant constraints are left out.
+    pub fn to_line(&self) -> String {
+        let size = self.geometry().size();
+        (0..size * size)
+            .map(|i| {
+                self.get(i / size, i % size)
+                    .map_or('.', |v| value_symbol(v.value()))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    /// Read a puzzle in the format described in the [crate
+    /// documentation](crate#puzzle-files).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Diagonals, Puzzle};
+    ///
+    /// let pzl: Puzzle = "
+    ///     grid
+    ///     1...
+    ///     ..3.
+    ///     ....
+    ///     ....
+    ///     diagonals
+    /// ".parse().unwrap();
+    /// assert_eq!(pzl.geometry().size(), 4);
+    /// assert!(pzl.constraint::<Diagonals>().is_some());
+    /// assert_eq!(pzl.to_string().parse::<Puzzle>().unwrap().to_string(), pzl.to_string());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = Lines {
+            inner: s.lines().enumerate(),
+        };
+        let mut boxes = None;
+        let mut grid = None;
+        let mut regions = None;
+        let (mut diagonals, mut anti_knight, mut anti_king) = (false, false, false);
+        let mut cages = Vec::new();
+        let mut thermos = Vec::new();
+        let mut arrows = Vec::new();
+        let mut sandwiches = Vec::new();
+        let mut dots = Vec::new();
+        let mut negative = Vec::new();
+        let (mut odd, mut even) = (Vec::new(), Vec::new());
+        let mut signs = Vec::new();
+        let mut whispers = Vec::new();
+        while let Some((n, line)) = lines.next() {
+            let mut words = line.split_whitespace();
+            let directive = words.next().unwrap();
+            let args: Vec<&str> = words.collect();
+            let cells = |args: &[&str]| -> Result<Vec<(usize, usize)>, ParseError> {
+                args.iter()
+                    .map(|word| parse_cell(word).ok_or(ParseError::BadArguments(n)))
+                    .collect()
+            };
+            let number = |word: &str| -> Result<usize, ParseError> {
+                word.parse().map_err(|_| ParseError::BadArguments(n))
+            };
+            let sum = |word: &str| -> Result<u32, ParseError> {
+                u32::try_from(number(word)?).map_err(|_| ParseError::BadArguments(n))
+            };
+            match (directive, args.as_slice()) {
+                ("boxes", [dims]) => {
+                    let (rows, cols) = dims.split_once('x').ok_or(ParseError::BadArguments(n))?;
+                    boxes = Some((number(rows)?, number(cols)?, n));
+                }
+                ("grid", []) => grid = Some((lines.block(n)?, n)),
+                ("regions", []) => regions = Some((lines.block(n)?, n)),
+                ("diagonals", []) => diagonals = true,
+                ("anti-knight", []) => anti_knight = true,
+                ("anti-king", []) => anti_king = true,
+                ("cage", [total, rest @ ..]) => cages.push(Cage::new(sum(total)?, cells(rest)?)),
+                ("thermo", rest) => thermos.push(cells(rest)?),
+                ("arrow", rest) => arrows.push(cells(rest)?),
+                ("sandwich", [line @ ("row" | "column"), index, total]) => {
+                    let index = number(index)?
+                        .checked_sub(1)
+                        .ok_or(ParseError::BadArguments(n))?;
+                    sandwiches.push(if *line == "row" {
+                        Sandwich::row(index, sum(total)?)
+                    } else {
+                        Sandwich::column(index, sum(total)?)
+                    });
+                }
+                (marker @ ("white" | "black" | "x" | "v"), [a, b]) => {
+                    let cell = |word: &str| parse_cell(word).ok_or(ParseError::BadArguments(n));
+                    let marker = parse_marker(marker).unwrap();
+                    dots.push(Dot::new(marker, cell(a)?, cell(b)?));
+                }
+                ("negative", markers @ [_, ..]) => {
+                    for word in markers {
+                        negative.push(parse_marker(word).ok_or(ParseError::BadArguments(n))?);
+                    }
+                }
+                ("odd", rest) => odd.extend(cells(rest)?),
+                ("even", rest) => even.extend(cells(rest)?),
+                ("greater", [a, b]) => {
+                    let cell = |word: &str| parse_cell(word).ok_or(ParseError::BadArguments(n));
+                    signs.push([cell(a)?, cell(b)?]);
+                }
+                ("whispers", rest) => whispers.push(cells(rest)?),
+                ("boxes" | "grid" | "regions" | "diagonals" | "anti-knight" | "anti-king", _)
+                | (
+                    "cage" | "sandwich" | "white" | "black" | "x" | "v" | "negative" | "greater",
+                    _,
+                ) => return Err(ParseError::BadArguments(n)),
+                _ => return Err(ParseError::UnknownDirective(n)),
+            }
+        }
+
+        let (grid, grid_line) = grid.ok_or(ParseError::MissingGrid)?;
+        let size = grid.size();
+        let geometry = match boxes {
+            Some((rows, cols, line)) => (rows.checked_mul(cols) == Some(size) && size <= MAX_SIZE)
+                .then(|| Geometry::new(rows, cols))
+                .ok_or(ParseError::BadArguments(line))?,
+            None => Geometry::for_size(size).ok_or(ParseError::BadGrid(grid_line))?,
+        };
+        let mut puzzle = Puzzle::new(geometry);
+        for ((row, col), &symbol) in grid.cells() {
+            match symbol_value(symbol).ok_or(ParseError::BadGrid(grid_line))? {
+                Some(val) if val as usize <= size => puzzle.pin(row, col, Value::new(val)),
+                Some(_) => return Err(ParseError::BadGrid(grid_line)),
+                None => {}
+            }
+        }
+        if let Some((map, line)) = regions {
+            if map.size() != size {
+                return Err(ParseError::BadGrid(line));
+            }
+            let mut symbols = Vec::new();
+            let map = map.map(|symbol| {
+                symbols
+                    .iter()
+                    .position(|&s| s == symbol)
+                    .unwrap_or_else(|| {
+                        symbols.push(symbol);
+                        symbols.len() - 1
+                    })
+            });
+            puzzle = puzzle.with_regions(Regions::new(map).map_err(ParseError::Regions)?);
+        }
+        if diagonals {
+            puzzle = puzzle.with_constraint(Diagonals);
+        }
+        if anti_knight {
+            puzzle = puzzle.with_constraint(AntiKnight);
+        }
+        if anti_king {
+            puzzle = puzzle.with_constraint(AntiKing);
+        }
+        if !cages.is_empty() {
+            puzzle = puzzle.with_constraint(Killer::new(size, cages).map_err(ParseError::Cages)?);
+        }
+        if !thermos.is_empty() {
+            let thermos = Thermometers::new(size, thermos).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(thermos);
+        }
+        if !arrows.is_empty() {
+            puzzle = puzzle.with_constraint(Arrows::new(size, arrows).map_err(ParseError::Shapes)?);
+        }
+        if !sandwiches.is_empty() {
+            let sandwiches = Sandwiches::new(size, sandwiches).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(sandwiches);
+        }
+        if !dots.is_empty() || !negative.is_empty() {
+            let dots = Dots::new(size, dots).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(negative.into_iter().fold(dots, Dots::with_negative));
+        }
+        if !odd.is_empty() || !even.is_empty() {
+            let parity = Parity::new(size, odd, even).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(parity);
+        }
+        if !signs.is_empty() {
+            let signs = GreaterThan::new(size, signs).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(signs);
+        }
+        if !whispers.is_empty() {
+            let whispers = Whispers::new(size, whispers).map_err(ParseError::Shapes)?;
+            puzzle = puzzle.with_constraint(whispers);
+        }
+        Ok(puzzle)
+    }
+}
+
+impl fmt::Display for Puzzle {
+    /// Write the puzzle in the format described in the [crate
+    /// documentation](crate#puzzle-files).  Constraints defined outside of this crate are
+    /// left out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let geometry = self.geometry();
+        let size = geometry.size();
+        if Geometry::for_size(size) != Some(geometry) {
+            writeln!(f, "boxes {}x{}", geometry.box_rows(), geometry.box_cols())?;
+        }
+        writeln!(f, "grid")?;
+        for row in 0..size {
+            let line: String = (0..size)
+                .map(|col| self.get(row, col).map_or('.', |v| value_symbol(v.value())))
+                .collect();
+            writeln!(f, "{line}")?;
+        }
+        if *self.regions() != Regions::boxes(geometry) {
+            writeln!(f, "regions")?;
+            for row in 0..size {
+                let line: String = (0..size)
+                    .map(|col| value_symbol(self.regions().region(row, col) as u8 + 1))
+                    .collect();
+                writeln!(f, "{line}")?;
+            }
+        }
+        for constraint in self.constraints() {
+            let any = constraint as &dyn std::any::Any;
+            if any.is::<Diagonals>() {
+                writeln!(f, "diagonals")?;
+            } else if any.is::<AntiKnight>() {
+                writeln!(f, "anti-knight")?;
+            } else if any.is::<AntiKing>() {
+                writeln!(f, "anti-king")?;
+            } else if let Some(killer) = any.downcast_ref::<Killer>() {
+                for cage in killer.cages() {
+                    write!(f, "cage {}", cage.sum())?;
+                    write_cells(f, cage.cells())?;
+                    writeln!(f)?;
+                }
+            } else if let Some(thermos) = any.downcast_ref::<Thermometers>() {
+                for path in thermos.paths() {
+                    write!(f, "thermo")?;
+                    write_cells(f, path)?;
+                    writeln!(f)?;
+                }
+            } else if let Some(arrows) = any.downcast_ref::<Arrows>() {
+                for arrow in arrows.arrows() {
+                    write!(f, "arrow")?;
+                    write_cells(f, arrow)?;
+                    writeln!(f)?;
+                }
+            } else if let Some(sandwiches) = any.downcast_ref::<Sandwiches>() {
+                for clue in sandwiches.clues() {
+                    let line = if clue.is_row() { "row" } else { "column" };
+                    writeln!(f, "sandwich {line} {} {}", clue.index() + 1, clue.sum())?;
+                }
+            } else if let Some(dots) = any.downcast_ref::<Dots>() {
+                for dot in dots.dots() {
+                    write!(f, "{}", marker_name(dot.marker()))?;
+                    write_cells(f, &dot.cells())?;
+                    writeln!(f)?;
+                }
+                if !dots.negative().is_empty() {
+                    write!(f, "negative")?;
+                    for &marker in dots.negative() {
+                        write!(f, " {}", marker_name(marker))?;
+                    }
+                    writeln!(f)?;
+                }
+            } else if let Some(parity) = any.downcast_ref::<Parity>() {
+                for (name, cells) in [("odd", parity.odd()), ("even", parity.even())] {
+                    if !cells.is_empty() {
+                        write!(f, "{name}")?;
+                        write_cells(f, cells)?;
+                        writeln!(f)?;
+                    }
+                }
+            } else if let Some(signs) = any.downcast_ref::<GreaterThan>() {
+                for pair in signs.pairs() {
+                    write!(f, "greater")?;
+                    write_cells(f, pair)?;
+                    writeln!(f)?;
+                }
+            } else if let Some(whispers) = any.downcast_ref::<Whispers>() {
+                for line in whispers.lines() {
+                    write!(f, "whispers")?;
+                    write_cells(f, line)?;
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CLASSIC_PUZZLE;
+
+    const VARIANTS: &str = "\
+boxes 3x2
+grid
+1.....
+...3..
+......
+..5...
+......
+.....6
+regions
+111222
+113222
+133444
+553444
+553666
+553666
+diagonals
+anti-king
+cage 7 r1c2 r1c3
+thermo r2c1 r3c1 r4c1
+arrow r6c6 r6c5 r5c5
+sandwich row 2 5
+sandwich column 6 0
+white r1c1 r2c1
+v r5c1 r5c2
+negative white
+odd r1c1 r6c6
+even r2c2
+greater r3c3 r3c4
+whispers r1c6 r2c5 r3c6
+";
+
+    #[test]
+    fn round_trip() {
+        let pzl: Puzzle = VARIANTS.parse().unwrap();
+        assert_eq!(pzl.geometry(), Geometry::new(3, 2));
+        assert_eq!(pzl.get(3, 2), Some(Value::new(5)));
+        assert_eq!(pzl.regions().region(4, 1), 4);
+        assert!(pzl.constraint::<Diagonals>().is_some());
+        assert!(pzl.constraint::<AntiKnight>().is_none());
+        assert_eq!(pzl.constraint::<Killer>().unwrap().cages()[0].sum(), 7);
+        assert_eq!(pzl.constraint::<Thermometers>().unwrap().paths().len(), 1);
+        assert_eq!(pzl.constraint::<Arrows>().unwrap().arrows()[0][0], (5, 5));
+        assert_eq!(
+            pzl.constraint::<Sandwiches>().unwrap().clues(),
+            [Sandwich::row(1, 5), Sandwich::column(5, 0)]
+        );
+        let dots = pzl.constraint::<Dots>().unwrap();
+        assert_eq!(dots.dots()[1], Dot::new(Marker::V, (4, 0), (4, 1)));
+        assert_eq!(dots.negative(), [Marker::White]);
+        assert_eq!(pzl.constraint::<Parity>().unwrap().odd(), [(0, 0), (5, 5)]);
+        assert_eq!(
+            pzl.constraint::<GreaterThan>().unwrap().pairs(),
+            [[(2, 2), (2, 3)]]
+        );
+        assert_eq!(pzl.constraint::<Whispers>().unwrap().lines()[0].len(), 3);
+        assert_eq!(pzl.to_string(), VARIANTS);
+    }
+
+    #[test]
+    fn classic_defaults() {
+        let text = "# Comment\n\ngrid\n5 3 . . 7 . . . .\n6 . . 1 9 5 . . .\n\
+            . 9 8 . . . . 6 .\n8 . . . 6 . . . 3\n4 . . 8 . 3 . . 1\n7 . . . 2 . . . 6\n\
+            . 6 . . . . 2 8 .\n. . . 4 1 9 . . 5\n. . . . 8 . . 7 9  # Last row\n";
+        let pzl: Puzzle = text.parse().unwrap();
+        assert_eq!(pzl.geometry(), Geometry::CLASSIC);
+        assert_eq!(*pzl.regions(), Regions::default());
+        assert_eq!(pzl.get(8, 8), Some(Value::new(9)));
+        assert!(pzl.to_string().starts_with("grid\n53..7....\n"));
+        assert_eq!(pzl.solutions().count(), 1);
+    }
+
+    #[test]
+    fn large_values() {
+        let mut pzl = Puzzle::new(Geometry::new(4, 4));
+        pzl.pin(0, 0, Value::new(16));
+        pzl.pin(0, 1, Value::new(10));
+        let text = pzl.to_string();
+        assert!(text.starts_with("grid\nGA.............."));
+        assert_eq!(
+            text.parse::<Puzzle>().unwrap().get(0, 0),
+            Some(Value::new(16))
+        );
+    }
+
+    #[test]
+    fn single_line() {
+        let line = CLASSIC_PUZZLE;
+        let pzl = Puzzle::from_line(line).unwrap();
+        assert_eq!(pzl.geometry(), Geometry::CLASSIC);
+        assert_eq!(pzl.get(0, 1), Some(Value::new(3)));
+        assert_eq!(pzl.to_line(), line);
+        let zeros = line.replace('.', "0");
+        assert_eq!(Puzzle::from_line(&zeros).unwrap().to_line(), line);
+        assert_eq!(
+            Puzzle::from_line(&line[1..]).err(),
+            Some(ParseError::BadGrid(1))
+        );
+        assert_eq!(
+            Puzzle::from_line("12.5").err(),
+            Some(ParseError::BadGrid(1))
+        );
+    }
+
+    #[test]
+    fn invalid_text() {
+        let parse = |text: &str| text.parse::<Puzzle>().err().expect(text);
+        assert_eq!(parse("diagonals"), ParseError::MissingGrid);
+        assert_eq!(parse("grid\n12\n3"), ParseError::BadGrid(1));
+        assert_eq!(parse("grid\n123\n.1\n..1"), ParseError::BadGrid(1));
+        assert_eq!(
+            parse("grid\n12..\n....\n....\n..5."),
+            ParseError::BadGrid(1)
+        );
+        assert_eq!(parse("grid\n..\n..\nfoo"), ParseError::UnknownDirective(4));
+        assert_eq!(
+            parse("grid\n..\n..\n\ndiagonals 2"),
+            ParseError::BadArguments(5)
+        );
+        assert_eq!(parse("cage 3 r1c0\ngrid\n.."), ParseError::BadArguments(1));
+        assert_eq!(
+            parse("boxes 3x2\ngrid\n....\n....\n....\n...."),
+            ParseError::BadArguments(1)
+        );
+        assert_eq!(
+            parse("boxes 18446744073709551615x2\ngrid\n..\n.."),
+            ParseError::BadArguments(1)
+        );
+        let wide = format!("boxes 2x13\ngrid\n{}", ".".repeat(26));
+        assert_eq!(parse(&wide), ParseError::BadGrid(2));
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\nregions\n1122\n1122\n3344\n3444"),
+            ParseError::Regions(RegionError::WrongCellCount(2))
+        );
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\ncage 9 r1c1 r1c2"),
+            ParseError::Cages(CageError::ImpossibleSum(0))
+        );
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\ncage 4294967301 r1c1 r1c2"),
+            ParseError::BadArguments(6)
+        );
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\nsandwich row 1 4294967296"),
+            ParseError::BadArguments(6)
+        );
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\nthermo r1c1"),
+            ParseError::Shapes(ShapeError::BadLength(0))
+        );
+        assert_eq!(
+            parse("grid\n....\n....\n....\n....\nx r1c1 r1c2"),
+            ParseError::Shapes(ShapeError::ImpossibleClue(0))
+        );
+        assert_eq!(parse("grid\n..\n..\nnegative"), ParseError::BadArguments(4));
+        assert_eq!(
+            parse("grid\n..\n..\ngreater r1c1"),
+            ParseError::BadArguments(4)
+        );
+        assert_eq!(
+            parse("grid\n..\n..\nnegative xv"),
+            ParseError::BadArguments(4)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::grid::Geometry;
+use crate::solgrid::SolutionGrid;
+use crate::{Puzzle, Value};
+
+/// Symmetry of the givens of a generated puzzle.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Symmetry {
+    /// Givens are placed anywhere.
+    #[default]
+    None,
+    /// The grid looks the same after a half-turn.
+    Rotational,
+    /// The grid looks the same in a vertical mirror.
+    Mirror,
+    /// The grid looks the same when flipped along its main diagonal.
+    Diagonal,
+}
+
+impl Symmetry {
+    /// Cells that hold givens together with a given cell in a grid of a given
+    /// size, the cell included.
+    fn orbit(self, size: usize, (row, col): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut cells = vec![(row, col)];
+        match self {
+            Symmetry::None => {}
+            Symmetry::Rotational => cells.push((size - 1 - row, size - 1 - col)),
+            Symmetry::Mirror => cells.push((row, size - 1 - col)),
+            Symmetry::Diagonal => cells.push((col, row)),
+        }
+        cells.sort();
+        cells.dedup();
+        cells
+    }
+
+    /// All the orbits of a grid of a given size, in reading order of their
+    /// first cell.
+    fn orbits(self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter_map(|cell| {
+                let orbit = self.orbit(size, cell);
+                (orbit[0] == cell).then_some(orbit)
+            })
+            .collect()
+    }
+}
+
+/// Small SplitMix64 pseudo-random generator, so that generation only
+/// depends on its seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index below `n`, which must not be 0.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generator of random puzzles with a single solution, from which no given,
+/// or set of symmetric givens, can be removed without losing uniqueness.
+///
+/// Each removal is checked with a full search, so the time taken grows quickly
+/// with the size of the grid: about a second for 16x16 grids, and much longer
+/// for 20x20 grids and beyond.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Generator, Geometry, Symmetry};
+///
+/// let generator = Generator::new(Geometry::new(2, 2))
+///     .with_seed(42)
+///     .with_symmetry(Symmetry::Rotational);
+/// let pzl = generator.generate();
+/// assert_eq!(pzl.solutions().count(), 1);
+/// for (row, col) in (0..4).flat_map(|r| (0..4).map(move |c| (r, c))) {
+///     assert_eq!(pzl.get(row, col).is_some(), pzl.get(3 - row, 3 - col).is_some());
+/// }
+/// // The same seed always gives the same puzzle.
+/// assert_eq!(generator.generate().to_line(), pzl.to_line());
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Generator {
+    geometry: Geometry,
+    seed: u64,
+    symmetry: Symmetry,
+}
+
+impl Generator {
+    /// Create a generator of puzzles of a given [`Geometry`], with a seed of 0
+    /// and no symmetry.
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            seed: 0,
+            symmetry: Symmetry::None,
+        }
+    }
+
+    /// Set the seed of the pseudo-random choices.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Set the symmetry of the givens.
+    pub fn with_symmetry(self, symmetry: Symmetry) -> Self {
+        Self { symmetry, ..self }
+    }
+
+    /// Generate a puzzle.  This only depends on the settings of the
+    /// generator.
+    pub fn generate(&self) -> Puzzle {
+        let mut rng = Rng(self.seed);
+        let solution = loop {
+            if let Some(solution) = random_solution(self.geometry, &mut rng) {
+                break solution;
+            }
+        };
+        let mut orbits = self.symmetry.orbits(self.geometry.size());
+        rng.shuffle(&mut orbits);
+        minimize(solution, orbits)
+    }
+}
+
+/// A random full grid, `None` if the random givens picked to get there do
+/// not lead to a solution quickly.
+fn random_solution(geometry: Geometry, rng: &mut Rng) -> Option<Puzzle> {
+    let size = geometry.size();
+    let mut puzzle = Puzzle::new(geometry);
+    let mut grid = SolutionGrid::try_from(&puzzle).ok()?;
+    // A few random givens, each consistent with the previous ones.
+    for _ in 0..size {
+        let row = rng.below(size);
+        let col = rng.below(size);
+        let candidates: Vec<Value> = grid.possible_values(row, col).into_iter().collect();
+        let val = candidates[rng.below(candidates.len())];
+        let mut next = grid.clone();
+        if next.pin(row, col, val).is_ok() && next.maximize_constraints().is_ok() {
+            grid = next;
+            puzzle.pin(row, col, val);
+        }
+    }
+    let solution = puzzle.solutions().with_budget(1000).next()?;
+    // The solver favours small values, shuffle them.
+    let mut values: Vec<u8> = (1..=size as u8).collect();
+    rng.shuffle(&mut values);
+    let solution = solution.map(|val| values[val as usize - 1]);
+    Some(Puzzle::from_grid(geometry, solution))
+}
+
+/// Remove the givens of each orbit in turn, as long as the puzzle keeps a
+/// single solution.
+fn minimize(mut puzzle: Puzzle, orbits: Vec<Vec<(usize, usize)>>) -> Puzzle {
+    for orbit in orbits {
+        let givens: Vec<_> = orbit.iter().map(|&(r, c)| puzzle.get(r, c)).collect();
+        if givens.iter().all(Option::is_none) {
+            continue;
+        }
+        for &(row, col) in &orbit {
+            puzzle.unpin(row, col);
+        }
+        if puzzle.solutions().take(2).count() != 1 {
+            for (&(row, col), given) in orbit.iter().zip(givens) {
+                if let Some(val) = given {
+                    puzzle.pin(row, col, val);
+                }
+            }
+        }
+    }
+    puzzle
+}
+
+impl Puzzle {
+    /// Remove givens in reading order as long as the puzzle keeps a single
+    /// solution, so that no given of the result can be removed.  A puzzle
+    /// without a single solution is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::Puzzle;
+    ///
+    /// let full = Puzzle::from_line("1234341221434321").unwrap();
+    /// let pzl = full.minimized();
+    /// assert_eq!(pzl.solutions().count(), 1);
+    /// assert!(pzl.to_line().contains('.'));
+    /// ```
+    pub fn minimized(&self) -> Puzzle {
+        if self.solutions().take(2).count() != 1 {
+            return self.clone();
+        }
+        minimize(self.clone(), Symmetry::None.orbits(self.geometry().size()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::classic_puzzle;
+
+    fn is_minimal(pzl: &Puzzle) -> bool {
+        let size = pzl.geometry().size();
+        (0..size)
+            .flat_map(|r| (0..size).map(move |c| (r, c)))
+            .filter(|&(r, c)| pzl.get(r, c).is_some())
+            .all(|(r, c)| {
+                let mut less = pzl.clone();
+                less.unpin(r, c);
+                less.solutions().take(2).count() == 2
+            })
+    }
+
+    #[test]
+    fn generated_puzzles_are_minimal() {
+        for seed in 0..3 {
+            let pzl = Generator::new(Geometry::CLASSIC).with_seed(seed).generate();
+            assert_eq!(pzl.solutions().take(2).count(), 1);
+            assert!(is_minimal(&pzl));
+        }
+        let first = Generator::new(Geometry::CLASSIC).with_seed(1).generate();
+        let second = Generator::new(Geometry::CLASSIC).with_seed(2).generate();
+        assert_ne!(first.to_line(), second.to_line());
+    }
+
+    #[test]
+    fn symmetric_givens() {
+        for symmetry in [Symmetry::Rotational, Symmetry::Mirror, Symmetry::Diagonal] {
+            let pzl = Generator::new(Geometry::new(2, 3))
+                .with_symmetry(symmetry)
+                .with_seed(7)
+                .generate();
+            assert_eq!(pzl.solutions().take(2).count(), 1);
+            for (row, col) in (0..6).flat_map(|r| (0..6).map(move |c| (r, c))) {
+                for (r, c) in symmetry.orbit(6, (row, col)) {
+                    assert_eq!(pzl.get(row, col).is_some(), pzl.get(r, c).is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn minimized_keeps_solution() {
+        let pzl = classic_puzzle();
+        let min = pzl.minimized();
+        assert!(is_minimal(&min));
+        assert_eq!(min.solutions().next(), pzl.solutions().next());
+        let empty = Puzzle::default();
+        assert_eq!(empty.minimized().to_line(), empty.to_line());
+    }
+}
@@ -0,0 +1,235 @@
+use std::ops::{Index, IndexMut};
+
+/// Largest supported grid size, i.e. number of rows, columns, and values.
+pub const MAX_SIZE: usize = 25;
+
+/// Dimensions of a sudoku grid.  A grid of size `n = box_rows * box_cols` has
+/// `n` rows, `n` columns, and `n` boxes of `box_rows` rows by `box_cols`
+/// columns.  Each of them must contain the values from 1 to `n` exactly once.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::Geometry;
+///
+/// // A 6x6 grid with boxes of 2 rows by 3 columns.
+/// let geom = Geometry::new(2, 3);
+/// assert_eq!(geom.size(), 6);
+/// assert_eq!(geom.box_index(3, 4), 3);
+/// assert_eq!(Geometry::for_size(6), Some(geom));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Geometry {
+    box_rows: usize,
+    box_cols: usize,
+}
+
+impl Geometry {
+    /// The classic 9x9 grid with 3x3 boxes.
+    pub const CLASSIC: Geometry = Geometry {
+        box_rows: 3,
+        box_cols: 3,
+    };
+
+    /// Create a geometry with boxes of `box_rows` rows by `box_cols` columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting size is 0 or larger than [`MAX_SIZE`].
+    pub fn new(box_rows: usize, box_cols: usize) -> Self {
+        let size = box_rows * box_cols;
+        assert!(
+            size > 0 && size <= MAX_SIZE,
+            "Grid size is from 1 to {MAX_SIZE}, got {size}."
+        );
+        Self { box_rows, box_cols }
+    }
+
+    /// The conventional geometry for a grid of a given size: boxes are as
+    /// square as possible, and wider than tall.  This is `None` if `size` is 0
+    /// or larger than [`MAX_SIZE`].
+    pub fn for_size(size: usize) -> Option<Self> {
+        if size == 0 || size > MAX_SIZE {
+            return None;
+        }
+        let box_rows = (1..=size)
+            .take_while(|r| r * r <= size)
+            .filter(|r| size % r == 0)
+            .last()?;
+        Some(Self::new(box_rows, size / box_rows))
+    }
+
+    /// Number of rows, columns, boxes, and values of the grid.
+    pub fn size(&self) -> usize {
+        self.box_rows * self.box_cols
+    }
+
+    /// Number of rows in a box.
+    pub fn box_rows(&self) -> usize {
+        self.box_rows
+    }
+
+    /// Number of columns in a box.
+    pub fn box_cols(&self) -> usize {
+        self.box_cols
+    }
+
+    /// Number of cells of the grid.
+    pub fn n_cells(&self) -> usize {
+        self.size() * self.size()
+    }
+
+    /// Index of the box containing a given cell, boxes being numbered from
+    /// left to right and top to bottom.  This is 0-indexed.
+    pub fn box_index(&self, row: usize, col: usize) -> usize {
+        (row / self.box_rows) * self.box_rows + col / self.box_cols
+    }
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+/// Sudoku-shaped array holding a given type.  Rows are accessed by indexing,
+/// so that `grid[row][col]` is the element at a given position.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::Grid;
+///
+/// let mut grid = Grid::filled(4, 0);
+/// grid[1][2] = 5;
+/// assert_eq!(grid.size(), 4);
+/// assert_eq!(grid[1], [0, 0, 5, 0]);
+/// assert_eq!(grid, [[0, 0, 0, 0], [0, 0, 5, 0], [0, 0, 0, 0], [0, 0, 0, 0]]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Grid<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid of a given size, with the element at each position
+    /// computed by `f(row, col)`.
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(size: usize, mut f: F) -> Self {
+        let cells = (0..size * size).map(|i| f(i / size, i % size)).collect();
+        Self { size, cells }
+    }
+
+    /// Create a grid of a given size, filled with a value.
+    pub fn filled(size: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            size,
+            cells: vec![value; size * size],
+        }
+    }
+
+    /// Number of rows and columns of the grid.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Iterate through the rows of the grid.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &[T]> {
+        self.cells.chunks_exact(self.size.max(1))
+    }
+
+    /// Iterate through the rows of the grid, mutably.
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [T]> {
+        self.cells.chunks_exact_mut(self.size.max(1))
+    }
+
+    /// Iterate through all the elements of the grid with their position, row
+    /// by row.
+    pub fn cells(&self) -> impl ExactSizeIterator<Item = ((usize, usize), &T)> {
+        let size = self.size;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, v)| ((i / size, i % size), v))
+    }
+
+    /// Apply a function to all the elements of the grid.
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Grid<U> {
+        Grid {
+            size: self.size,
+            cells: self.cells.into_iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Default> Default for Grid<T> {
+    /// An empty grid of the classic 9x9 size.
+    fn default() -> Self {
+        Self::from_fn(Geometry::CLASSIC.size(), |_, _| T::default())
+    }
+}
+
+impl<T> Index<usize> for Grid<T> {
+    type Output = [T];
+
+    fn index(&self, row: usize) -> &[T] {
+        &self.cells[row * self.size..(row + 1) * self.size]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.cells[row * self.size..(row + 1) * self.size]
+    }
+}
+
+impl<T, const N: usize> From<[[T; N]; N]> for Grid<T> {
+    fn from(arr: [[T; N]; N]) -> Self {
+        Self {
+            size: N,
+            cells: arr.into_iter().flatten().collect(),
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[[T; N]; N]> for Grid<T> {
+    fn eq(&self, other: &[[T; N]; N]) -> bool {
+        self.size == N && self.cells.iter().eq(other.iter().flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conventional_geometries() {
+        let boxes = |size| Geometry::for_size(size).map(|g| (g.box_rows(), g.box_cols()));
+        assert_eq!(boxes(4), Some((2, 2)));
+        assert_eq!(boxes(6), Some((2, 3)));
+        assert_eq!(boxes(9), Some((3, 3)));
+        assert_eq!(boxes(12), Some((3, 4)));
+        assert_eq!(boxes(16), Some((4, 4)));
+        assert_eq!(boxes(25), Some((5, 5)));
+        assert_eq!(boxes(0), None);
+        assert_eq!(boxes(26), None);
+    }
+
+    #[test]
+    fn rectangular_box_index() {
+        let geom = Geometry::new(2, 3);
+        let boxes: Vec<_> = (0..6).map(|row| geom.box_index(row, 5)).collect();
+        assert_eq!(boxes, [1, 1, 3, 3, 5, 5]);
+    }
+
+    #[test]
+    fn rows_of_grid() {
+        let grid = Grid::from_fn(3, |row, col| 3 * row + col);
+        let rows: Vec<_> = grid.iter().collect();
+        assert_eq!(rows, [[0, 1, 2], [3, 4, 5], [6, 7, 8]]);
+        assert_eq!(grid.cells().nth(5), Some(((1, 2), &5)));
+    }
+}
@@ -8,14 +8,79 @@
 //! [`Puzzle`] with at least one solution if you were to add it as an additional
 //! clue.
 //!
+//! Puzzles made of several grids sharing some of their boxes, such as Samurai
+//! sudoku, are solved jointly with a [`MultiPuzzle`].
+//!
+//! New puzzles with a single solution come from a [`Generator`].  Existing
+//! ones can be rated with [`Puzzle::rate`], stripped of the givens they do not
+//! need with [`Puzzle::minimized`], and compared with [`Puzzle::canonical`].
+//! Puzzles with several solutions can be finished with the givens of
+//! [`Puzzle::suggest_givens`].  The cells that are the same in all solutions
+//! are given by [`Puzzle::backbone`], and the givens that cannot be left out
+//! by [`Puzzle::essential_givens`].
+//!
+//! A puzzle in the course of being solved, with pencil marks, is a
+//! [`CandidateGrid`]: its solutions only use the candidates left, and
+//! [`CandidateGrid::apply_hint`] makes the next logical deduction.
+//!
+//! # Puzzle files
+//!
+//! A [`Puzzle`] with its variant constraints can be read from and written to
+//! text with [`str::parse`] and [`ToString::to_string`].
+//! Lines are directives, blank lines and text following a `#` are ignored.
+//! Positions are given as `r<row>c<col>`, 1-indexed, and values above 9 are
+//! written with letters from `A` for 10.
+//!
+//! ```text
+//! # Optional box dimensions, the conventional ones for the size by default.
+//! boxes 3x3
+//! # The givens, one line per row, `.` or `0` for empty cells.
+//! grid
+//! 53..7....
+//! 6..195...
+//! .98....6.
+//! 8...6...3
+//! 4..8.3..1
+//! 7...2...6
+//! .6....28.
+//! ...419..5
+//! ....8..79
+//! # Optional jigsaw regions, one symbol per region.
+//! regions
+//! 111222333
+//! ...
+//! # Variant constraints.
+//! diagonals
+//! anti-knight
+//! anti-king
+//! cage 15 r1c1 r1c2 r2c1
+//! thermo r1c1 r1c2 r1c3
+//! arrow r5c5 r4c5 r3c5
+//! sandwich row 1 15
+//! sandwich column 4 0
+//! # Markers between adjacent cells: white, black, x, or v.
+//! white r1c1 r1c2
+//! x r2c1 r3c1
+//! # Pairs without markers do not satisfy the relations listed.
+//! negative white black
+//! # Odd and even cells.
+//! odd r1c1 r9c9
+//! even r5c5
+//! # The first cell is larger than the second.
+//! greater r1c1 r1c2
+//! whispers r1c1 r2c2 r2c3
+//! ```
+//!
 //! # Known caveats
 //!
 //! The solving strategy is fairly naive. This is a backtracking algorithm with
-//! two simple ingredients:
+//! three simple ingredients:
 //!
 //! - when making a guess in a cell (the puzzle is considered as a set of
 //!   guesses that are valid), the guessed value is removed as a viable
 //!   candidate in the relevant row, column, and block of the puzzle;
+//! - a value that has only one viable cell left in a row, column, or block
+//!   is placed there;
 //! - when a guess has to be made, the cell with the fewest candidate values is
 //!   picked.
 //!
@@ -42,10 +107,40 @@
 //! blocking calls to the solver in case you run into such a case.  See
 //! [`SolutionIterator::with_handle`] for how to call the solver in a separate
 //! thread and signal it to stop.
+mod analysis;
+mod candidates;
+mod canonical;
+mod check;
+mod constraint;
+mod finder;
+#[cfg(test)]
+mod fixtures;
+mod format;
+mod generate;
+mod grid;
+mod multi;
 mod puzzle;
+mod rating;
+mod regions;
 mod soft;
 mod solgrid;
 mod solver;
+mod unavoidable;
+mod variants;
 
-pub use puzzle::{Grid, Puzzle, Value};
-pub use solver::{PossibleValuesFinder, SolutionIterator};
+pub use candidates::{CandidateGrid, CandidateIter, CandidateSet};
+pub use check::{Uniqueness, Unit, Violation};
+pub use constraint::{Constraint, Contradiction, GridState, LatinSquare};
+pub use finder::PossibleValuesFinder;
+pub use format::ParseError;
+pub use generate::{Generator, Symmetry};
+pub use grid::{Geometry, Grid, MAX_SIZE};
+pub use multi::{MultiPuzzle, MultiSolutionIterator};
+pub use puzzle::{Edit, Puzzle, Value};
+pub use rating::{Rating, Technique};
+pub use regions::{RegionError, Regions};
+pub use solver::{SearchOutcome, SnapshotError, SolutionIterator};
+pub use variants::{
+    AntiKing, AntiKnight, Arrows, Cage, CageError, Diagonals, Dot, Dots, GreaterThan, Killer,
+    Marker, Parity, Sandwich, Sandwiches, ShapeError, Thermometers, Whispers,
+};
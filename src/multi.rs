@@ -0,0 +1,408 @@
+use crate::grid::{Geometry, Grid};
+use crate::solgrid::{SolResult, SolutionGrid};
+use crate::{CandidateSet, Puzzle, Value};
+use std::iter::FusedIterator;
+
+/// A cell of one of the grids of a [`MultiPuzzle`]: grid index, row and
+/// column, all 0-indexed.
+type Cell = (usize, usize, usize);
+
+/// A puzzle made of several grids sharing some of their boxes, such as
+/// Samurai sudoku.  The grids are solved jointly: shared cells hold the same
+/// value in all the grids they belong to.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{MultiPuzzle, Puzzle};
+///
+/// // Two classic grids, the last box of the first being the first box of
+/// // the second.
+/// let pzl = MultiPuzzle::new(vec![Puzzle::default(), Puzzle::default()])
+///     .with_overlap((0, 8), (1, 0));
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_eq!(sol[0][8][8], sol[1][2][2]);
+/// ```
+#[derive(Default, Clone)]
+pub struct MultiPuzzle {
+    grids: Vec<Puzzle>,
+    /// Pairs of cells of different grids that are the same.
+    links: Vec<(Cell, Cell)>,
+}
+
+impl MultiPuzzle {
+    /// Create a puzzle from independent grids, see [`Self::with_overlap`] to
+    /// share their boxes.
+    pub fn new(grids: Vec<Puzzle>) -> Self {
+        Self {
+            grids,
+            links: Vec::new(),
+        }
+    }
+
+    /// The five grids of a Samurai sudoku: the four corner grids in reading
+    /// order, each sharing a corner box with the center grid, then the center
+    /// grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grids are not classic 9x9 grids.
+    pub fn samurai(grids: [Puzzle; 5]) -> Self {
+        assert!(
+            grids.iter().all(|g| g.geometry() == Geometry::CLASSIC),
+            "Samurai grids are classic 9x9 grids."
+        );
+        Self::new(grids.into())
+            .with_overlap((0, 8), (4, 0))
+            .with_overlap((1, 6), (4, 2))
+            .with_overlap((2, 2), (4, 6))
+            .with_overlap((3, 0), (4, 8))
+    }
+
+    /// Declare that two boxes, given as `(grid, box)` indices, are the same
+    /// cells.  Boxes are numbered in reading order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a grid or box does not exist, or if the boxes of the two grids
+    /// have different dimensions.
+    pub fn with_overlap(mut self, first: (usize, usize), second: (usize, usize)) -> Self {
+        let box_cells = |(grid, index): (usize, usize)| {
+            let geometry = self.grids[grid].geometry();
+            assert!(index < geometry.size(), "No box {index} in grid {grid}.");
+            let size = geometry.size();
+            let cells: Vec<Cell> = (0..size)
+                .flat_map(|row| (0..size).map(move |col| (grid, row, col)))
+                .filter(|&(_, row, col)| geometry.box_index(row, col) == index)
+                .collect();
+            (geometry, cells)
+        };
+        let (geometry, first) = box_cells(first);
+        let (other, second) = box_cells(second);
+        assert_eq!(geometry, other, "Overlapping boxes have different shapes.");
+        self.links.extend(first.into_iter().zip(second));
+        self
+    }
+
+    /// The grids of the puzzle.
+    pub fn grids(&self) -> &[Puzzle] {
+        &self.grids
+    }
+
+    /// Iterate through the solutions of the puzzle, each given as the
+    /// solutions of the grids.
+    pub fn solutions(&self) -> MultiSolutionIterator {
+        MultiSolutionIterator::new(self)
+    }
+
+    /// Compute the set of values in each cell of each grid that lead to a
+    /// solvable puzzle.
+    ///
+    /// As with [`PossibleValuesFinder`], values seen in a solution are marked
+    /// as possible right away.  Each remaining value is then probed on its
+    /// own, and removed from the shared starting grids if no solution is
+    /// found.
+    ///
+    /// [`PossibleValuesFinder`]: crate::PossibleValuesFinder
+    pub fn possible_values(&self) -> Vec<Grid<CandidateSet>> {
+        let mut confirmed: Vec<Grid<CandidateSet>> = self
+            .grids
+            .iter()
+            .map(|g| Grid::filled(g.geometry().size(), CandidateSet::new()))
+            .collect();
+        let Some(mut root) = MultiGrid::new(self) else {
+            return confirmed;
+        };
+        let cells: Vec<Cell> = confirmed
+            .iter()
+            .enumerate()
+            .flat_map(|(grid, pvs)| pvs.cells().map(move |((row, col), _)| (grid, row, col)))
+            .collect();
+        for (grid, row, col) in cells {
+            for val in root.grids[grid].possible_values(row, col) {
+                if confirmed[grid][row][col].contains(val.value()) {
+                    continue;
+                }
+                let mut probe = root.clone();
+                let found = probe.pin((grid, row, col), val).is_ok()
+                    && probe.propagate(&self.links).is_ok()
+                    && MultiSolutionIterator::from_root(self, probe)
+                        .next()
+                        .map(|sol| {
+                            for (pvs, sol) in confirmed.iter_mut().zip(sol) {
+                                for ((row, col), &val) in sol.cells() {
+                                    pvs[row][col].insert(val);
+                                }
+                            }
+                        })
+                        .is_some();
+                if !found
+                    && (root.forbid((grid, row, col), val).is_err()
+                        || root.propagate(&self.links).is_err())
+                {
+                    // Only possible if the puzzle has no solution at all.
+                    return confirmed;
+                }
+            }
+        }
+        confirmed
+    }
+}
+
+/// Search state of all the grids of a [`MultiPuzzle`].
+#[derive(Clone)]
+struct MultiGrid {
+    grids: Vec<SolutionGrid>,
+}
+
+impl MultiGrid {
+    /// The propagated grids of a puzzle, `None` if it has no solution.
+    fn new(puzzle: &MultiPuzzle) -> Option<Self> {
+        let grids = puzzle
+            .grids
+            .iter()
+            .map(SolutionGrid::try_from)
+            .collect::<SolResult<_>>()
+            .ok()?;
+        let mut multi = Self { grids };
+        multi.propagate(&puzzle.links).ok()?;
+        Some(multi)
+    }
+
+    fn pin(&mut self, (grid, row, col): Cell, val: Value) -> SolResult<()> {
+        self.grids[grid].pin(row, col, val)
+    }
+
+    fn forbid(&mut self, (grid, row, col): Cell, val: Value) -> SolResult<()> {
+        self.grids[grid].forbid(row, col, val)
+    }
+
+    /// Propagate the constraints of each grid, and remove the candidates of
+    /// shared cells that are not candidates in all the grids, until nothing
+    /// changes.
+    fn propagate(&mut self, links: &[(Cell, Cell)]) -> SolResult<()> {
+        loop {
+            for grid in &mut self.grids {
+                grid.maximize_constraints()?;
+            }
+            let mut changed = false;
+            for &(a, b) in links {
+                let cands_a = self.grids[a.0].candidates(a.1, a.2);
+                let cands_b = self.grids[b.0].candidates(b.1, b.2);
+                for val in cands_a.difference(cands_b).all_values() {
+                    self.forbid(a, val)?;
+                    changed = true;
+                }
+                for val in cands_b.difference(cands_a).all_values() {
+                    self.forbid(b, val)?;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break Ok(());
+            }
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.grids.iter().all(SolutionGrid::is_solved)
+    }
+}
+
+/// Iterate through the solutions of a given [`MultiPuzzle`].  Instances are
+/// obtained via [`MultiPuzzle::solutions`].
+#[derive(Clone)]
+pub struct MultiSolutionIterator {
+    links: Vec<(Cell, Cell)>,
+    stack: Vec<MultiGrid>,
+}
+
+impl MultiSolutionIterator {
+    fn new(puzzle: &MultiPuzzle) -> Self {
+        Self {
+            links: puzzle.links.clone(),
+            stack: MultiGrid::new(puzzle).into_iter().collect(),
+        }
+    }
+
+    fn from_root(puzzle: &MultiPuzzle, root: MultiGrid) -> Self {
+        Self {
+            links: puzzle.links.clone(),
+            stack: vec![root],
+        }
+    }
+
+    fn propagate_and_push(&mut self, mut grid: MultiGrid) {
+        if grid.propagate(&self.links).is_ok() {
+            self.stack.push(grid);
+        }
+    }
+}
+
+impl Iterator for MultiSolutionIterator {
+    type Item = Vec<Grid<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(mut multi) = self.stack.pop() {
+            if multi.is_solved() {
+                let sols = multi.grids.into_iter().map(|g| g.try_into().unwrap());
+                return Some(sols.collect());
+            }
+            // Branch on the cell with the fewest candidates of all the grids.
+            let least = multi
+                .grids
+                .iter()
+                .enumerate()
+                .filter_map(|(i, g)| g.find_least_sols_fuzzy().map(|(r, c, sc)| ((i, r, c), sc)))
+                .min_by_key(|(_, sc)| sc.num_solutions());
+            if let Some((cell, sc)) = least {
+                if let Some(val) = sc.smallest_solution() {
+                    let mut new_multi = multi.clone();
+                    if multi.forbid(cell, val).is_ok() {
+                        self.propagate_and_push(multi);
+                    }
+                    if new_multi.pin(cell, val).is_ok() {
+                        self.propagate_and_push(new_multi);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FusedIterator for MultiSolutionIterator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: [&str; 9]) -> Puzzle {
+        format!("grid\n{}", rows.join("\n")).parse().unwrap()
+    }
+
+    /// A Samurai sudoku none of whose grids has a single solution alone.
+    fn samurai() -> MultiPuzzle {
+        MultiPuzzle::samurai([
+            grid([
+                "....5...9",
+                "4....9.2.",
+                "...123.5.",
+                ".31..4...",
+                ".7.....6.",
+                ".94538...",
+                ".........",
+                ".........",
+                "..8......",
+            ]),
+            grid([
+                ".3....7..",
+                ".8...9..5",
+                "..5..83..",
+                "......9..",
+                ".......1.",
+                ".5..1..68",
+                "1....5.9.",
+                "...6.1...",
+                ".6.9..5..",
+            ]),
+            grid([
+                "...7.....",
+                "5.9..4.6.",
+                "........9",
+                "...8.....",
+                "...4.5.1.",
+                "89.2....6",
+                "..5.3.9..",
+                ".7....2..",
+                ".3...8...",
+            ]),
+            grid([
+                ".8.1.....",
+                ".9......6",
+                ".......2.",
+                ".2.75....",
+                "7..98.6.4",
+                ".4...6...",
+                "......89.",
+                ".....1..2",
+                "..8.45.3.",
+            ]),
+            grid([
+                "......1..",
+                "...2.....",
+                "....3..6.",
+                "....7....",
+                "7..91....",
+                "...3.6...",
+                ".......8.",
+                ".6.4...9.",
+                "..9......",
+            ]),
+        ])
+    }
+
+    #[test]
+    fn samurai_single_sol() {
+        let pzl = samurai();
+        for grid in pzl.grids() {
+            assert_eq!(grid.solutions().take(2).count(), 2);
+        }
+        let mut sols = pzl.solutions();
+        let sol = sols.next().unwrap();
+        assert!(sols.next().is_none());
+        assert_eq!(sol.len(), 5);
+        for (grid, sol) in pzl.grids().iter().zip(&sol) {
+            let mut solved = Puzzle::default();
+            for ((row, col), &val) in sol.cells() {
+                assert!(grid.get(row, col).map_or(true, |v| v.value() == val));
+                solved.pin(row, col, Value::new(val));
+            }
+            assert_eq!(solved.solutions().next().as_ref(), Some(sol));
+        }
+        for (corner, (row, col), (center_row, center_col)) in [
+            (0, (6, 6), (0, 0)),
+            (1, (6, 0), (0, 6)),
+            (2, (0, 6), (6, 0)),
+            (3, (0, 0), (6, 6)),
+        ] {
+            for (r, c) in (0..3).flat_map(|r| (0..3).map(move |c| (r, c))) {
+                assert_eq!(
+                    sol[corner][row + r][col + c],
+                    sol[4][center_row + r][center_col + c]
+                );
+            }
+        }
+        let pvals = pzl.possible_values();
+        for (pvals, sol) in pvals.iter().zip(&sol) {
+            for ((row, col), vals) in pvals.cells() {
+                assert_eq!(*vals, CandidateSet::from([sol[row][col]]));
+            }
+        }
+    }
+
+    #[test]
+    fn possible_values_of_shared_cells() {
+        // Two 4x4 grids sharing a box, the first grid pinning its value.
+        let mut first = Puzzle::new(Geometry::new(2, 2));
+        first.pin(2, 2, Value::new(3));
+        let second = Puzzle::new(Geometry::new(2, 2));
+        let pzl = MultiPuzzle::new(vec![first, second]).with_overlap((0, 3), (1, 0));
+        let sols: Vec<_> = pzl.solutions().collect();
+        assert!(sols.iter().all(|sol| sol[1][0][0] == 3));
+        let pvals = pzl.possible_values();
+        for (grid, pvals) in pvals.iter().enumerate() {
+            for ((row, col), vals) in pvals.cells() {
+                let expected: CandidateSet = sols.iter().map(|sol| sol[grid][row][col]).collect();
+                assert_eq!(*vals, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn overlap_of_different_boxes() {
+        let pzl = MultiPuzzle::new(vec![Puzzle::default(), Puzzle::new(Geometry::new(2, 2))]);
+        let _ = pzl.with_overlap((0, 0), (1, 0));
+    }
+}
@@ -1,12 +1,16 @@
-use std::collections::BTreeSet;
+use std::any::Any;
 use std::num::NonZeroU8;
+use std::sync::Arc;
 
-use crate::solver::{PossibleValuesFinder, SolutionIterator};
+use crate::candidates::CandidateSet;
+use crate::constraint::{Constraint, LatinSquare};
+use crate::finder::PossibleValuesFinder;
+use crate::grid::{Geometry, Grid, MAX_SIZE};
+use crate::regions::Regions;
+use crate::solver::SolutionIterator;
 
-/// Sudoku-shaped array holding a given type.
-pub type Grid<T> = [[T; 9]; 9];
-
-/// Represent a valid cell value, i.e. an integer between 1 and 9 inclusive.
+/// Represent a valid cell value, i.e. an integer between 1 and [`MAX_SIZE`]
+/// inclusive.  Whether a value fits in a given grid depends on its size.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Ord, PartialOrd)]
 pub struct Value(NonZeroU8);
 
@@ -15,7 +19,7 @@ impl Value {
     ///
     /// # Panics
     ///
-    /// Panics if `val` is not between 1 and 9 inclusive.
+    /// Panics if `val` is not between 1 and [`MAX_SIZE`] inclusive.
     ///
     /// # Examples
     ///
@@ -30,11 +34,14 @@ impl Value {
     /// let val = Value::new(0);
     /// ```
     pub fn new(val: u8) -> Self {
-        assert!(val > 0 && val < 10, "Cell value is from 1 to 9, got {val}.");
+        assert!(
+            val > 0 && val as usize <= MAX_SIZE,
+            "Cell value is from 1 to {MAX_SIZE}, got {val}."
+        );
         Self(val.try_into().unwrap())
     }
 
-    /// The cell value, always between 1 and 9 inclusive.
+    /// The cell value, always between 1 and [`MAX_SIZE`] inclusive.
     ///
     /// # Examples
     ///
@@ -48,9 +55,23 @@ impl Value {
     }
 }
 
+/// A single change made to a [`Puzzle`], see [`Puzzle::apply`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Edit {
+    /// Set the [`Value`] at a given position, see [`Puzzle::pin`].
+    Pin(usize, usize, Value),
+    /// Unset the [`Value`] at a given position, see [`Puzzle::unpin`].
+    Unpin(usize, usize),
+}
+
 /// A sudoku grid puzzle.  This type is the main API of the library as it
 /// allows you to define your sudoku puzzle and solve it.
 ///
+/// Puzzles are classic 9x9 grids by default, other sizes are described by a
+/// [`Geometry`], see [`Puzzle::new`].  Jigsaw puzzles replace the boxes by
+/// irregular [`Regions`], see [`Puzzle::with_regions`].  Variants add their
+/// own rules as [`Constraint`], see [`Puzzle::with_constraint`].
+///
 /// # Note
 ///
 /// The [`Puzzle`] doesn't have to be well-posed: it can have 0 or several
@@ -92,14 +113,45 @@ impl Value {
 /// assert_eq!(sol, expected);
 /// ```
 #[derive(Default, Clone)]
-pub struct Puzzle(Grid<Option<Value>>);
+pub struct Puzzle {
+    geometry: Geometry,
+    regions: Regions,
+    constraints: Vec<Arc<dyn Constraint>>,
+    grid: Grid<Option<Value>>,
+}
 
 impl Puzzle {
-    /// Build a [`Puzzle`] from an array of values.  Zeros are seen as non-constrained cells.
+    /// Create an empty puzzle of a given [`Geometry`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Geometry, Puzzle, Value};
+    ///
+    /// // A 6x6 puzzle, with boxes of 2 rows by 3 columns.
+    /// let mut pzl = Puzzle::new(Geometry::new(2, 3));
+    /// pzl.pin(0, 0, Value::new(6));
+    /// let sol = pzl.solutions().next().unwrap();
+    /// assert_eq!(sol.size(), 6);
+    /// assert_eq!(sol[0], [6, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            regions: Regions::boxes(geometry),
+            constraints: Vec::new(),
+            grid: Grid::filled(geometry.size(), None),
+        }
+    }
+
+    /// Build a [`Puzzle`] from an array of values.  Zeros are seen as
+    /// non-constrained cells.  The geometry of the puzzle is the conventional
+    /// one for the size of the array, see [`Geometry::for_size`].
     ///
     /// # Panics
     ///
-    /// Panics if an element is not between 0 and 9 inclusive.
+    /// Panics if the size of the array is not supported, or if an element is
+    /// larger than that size.
     ///
     /// # Example
     ///
@@ -112,24 +164,212 @@ impl Puzzle {
     /// assert_eq!(pzl.get(2, 4).unwrap(), Value::new(1));
     /// assert_eq!(pzl.get(0, 0), None);
     /// ```
-    pub fn from_arr(arr: Grid<u8>) -> Self {
-        let inner = arr.map(|row| row.map(|v| (v != 0).then(|| Value::new(v))));
-        Puzzle(inner)
+    pub fn from_arr<A: Into<Grid<u8>>>(arr: A) -> Self {
+        let grid = arr.into();
+        let geometry = Geometry::for_size(grid.size())
+            .unwrap_or_else(|| panic!("Unsupported grid size {}.", grid.size()));
+        Self::from_grid(geometry, grid)
+    }
+
+    /// Build a [`Puzzle`] of a given [`Geometry`] from a grid of values.
+    /// Zeros are seen as non-constrained cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the grid does not match the geometry, or if an
+    /// element is larger than that size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Geometry, Puzzle};
+    ///
+    /// // Six rows of six cells, with boxes of 3 rows by 2 columns.
+    /// let mut arr = [[0; 6]; 6];
+    /// arr[0] = [1, 2, 3, 4, 5, 6];
+    /// let pzl = Puzzle::from_grid(Geometry::new(3, 2), arr.into());
+    /// assert_eq!(pzl.solutions().next().unwrap()[1][..2], [3, 4]);
+    /// ```
+    pub fn from_grid(geometry: Geometry, grid: Grid<u8>) -> Self {
+        assert_eq!(
+            grid.size(),
+            geometry.size(),
+            "Grid size does not match the geometry."
+        );
+        let mut pzl = Self::new(geometry);
+        for ((row, col), &v) in grid.cells() {
+            if v != 0 {
+                pzl.pin(row, col, Value::new(v));
+            }
+        }
+        pzl
+    }
+
+    /// Replace the boxes of the puzzle by arbitrary [`Regions`], turning it
+    /// into a jigsaw puzzle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the regions does not match the size of the
+    /// puzzle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, Regions};
+    ///
+    /// let regions = Regions::new([
+    ///     [0, 0, 0, 1],
+    ///     [2, 0, 1, 1],
+    ///     [2, 2, 3, 1],
+    ///     [2, 3, 3, 3],
+    /// ]).unwrap();
+    /// let pzl = Puzzle::from_arr([
+    ///     [0, 0, 3, 0],
+    ///     [0, 0, 0, 0],
+    ///     [0, 1, 0, 2],
+    ///     [0, 0, 0, 0],
+    /// ]).with_regions(regions);
+    /// let mut sols = pzl.solutions();
+    /// let expected = [[1, 2, 3, 4], [2, 4, 1, 3], [3, 1, 4, 2], [4, 3, 2, 1]];
+    /// assert_eq!(sols.next().unwrap(), expected);
+    /// assert!(sols.next().is_none());
+    /// ```
+    pub fn with_regions(self, regions: Regions) -> Self {
+        assert_eq!(
+            regions.size(),
+            self.geometry.size(),
+            "Regions size does not match the puzzle."
+        );
+        Self { regions, ..self }
+    }
+
+    /// Add a [`Constraint`] to the rules of the puzzle, on top of the classic
+    /// rules and those added before.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Constraint, Puzzle};
+    ///
+    /// /// The first two cells of the grid must hold distinct values, which
+    /// /// the classic rules already imply.
+    /// struct TopLeftPair;
+    ///
+    /// impl Constraint for TopLeftPair {
+    ///     fn groups(&self, _size: usize) -> Vec<Vec<(usize, usize)>> {
+    ///         vec![vec![(0, 0), (0, 1)]]
+    ///     }
+    /// }
+    ///
+    /// let pzl = Puzzle::default().with_constraint(TopLeftPair);
+    /// assert!(pzl.constraint::<TopLeftPair>().is_some());
+    /// assert_eq!(pzl.constraints().count(), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constraint is made for grids of another size, see
+    /// [`Constraint::size`].
+    pub fn with_constraint<C: Constraint>(mut self, constraint: C) -> Self {
+        if let Some(size) = constraint.size() {
+            assert_eq!(
+                size,
+                self.geometry.size(),
+                "Constraint size does not match the puzzle."
+            );
+        }
+        self.constraints.push(Arc::new(constraint));
+        self
+    }
+
+    /// Remove all the constraints of a given type added by
+    /// [`Self::with_constraint`].
+    pub fn without_constraint<C: Constraint>(mut self) -> Self {
+        self.constraints
+            .retain(|c| !(c.as_ref() as &dyn Any).is::<C>());
+        self
+    }
+
+    /// Iterate through the rules of the puzzle: [`LatinSquare`], its
+    /// [`Regions`], and the constraints added by [`Self::with_constraint`].
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        [&LatinSquare as &dyn Constraint, &self.regions]
+            .into_iter()
+            .chain(self.added_constraints())
+    }
+
+    /// Iterate through the constraints added by [`Self::with_constraint`].
+    pub(crate) fn added_constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        self.constraints.iter().map(|c| c.as_ref())
+    }
+
+    /// The first constraint of a given type added by
+    /// [`Self::with_constraint`], if any.
+    pub fn constraint<C: Constraint>(&self) -> Option<&C> {
+        self.constraints
+            .iter()
+            .find_map(|c| (c.as_ref() as &dyn Any).downcast_ref())
+    }
+
+    /// All the rules of the puzzle, to be shared with the solver.
+    pub(crate) fn shared_constraints(&self) -> Vec<Arc<dyn Constraint>> {
+        let classic: [Arc<dyn Constraint>; 2] =
+            [Arc::new(LatinSquare), Arc::new(self.regions.clone())];
+        classic
+            .into_iter()
+            .chain(self.constraints.iter().cloned())
+            .collect()
+    }
+
+    /// The [`Geometry`] of the puzzle.  Its boxes are only meaningful if the
+    /// puzzle is not a jigsaw, see [`Self::regions`].
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// The [`Regions`] of the puzzle, which are the boxes of its
+    /// [`Geometry`] unless set by [`Self::with_regions`].
+    pub fn regions(&self) -> &Regions {
+        &self.regions
     }
 
     /// Get the [`Value`] at a given position.  This is 0-indexed.
     pub fn get(&self, row: usize, col: usize) -> Option<Value> {
-        self.0[row][col]
+        self.grid[row][col]
     }
 
     /// Set the [`Value`] at a given position.  This is 0-indexed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is larger than the size of the grid.
     pub fn pin(&mut self, row: usize, col: usize, val: Value) {
-        self.0[row][col] = Some(val);
+        assert!(
+            val.value() as usize <= self.geometry.size(),
+            "Cell value is from 1 to {}, got {}.",
+            self.geometry.size(),
+            val.value()
+        );
+        self.grid[row][col] = Some(val);
     }
 
     /// Unset the [`Value`] at a given position.  This is 0-indexed.
     pub fn unpin(&mut self, row: usize, col: usize) {
-        self.0[row][col] = None;
+        self.grid[row][col] = None;
+    }
+
+    /// Iterate through all the cells of the puzzle with their position.
+    pub(crate) fn cells(&self) -> impl Iterator<Item = ((usize, usize), &Option<Value>)> {
+        self.grid.cells()
+    }
+
+    /// Apply an [`Edit`] to the puzzle.
+    pub fn apply(&mut self, edit: Edit) {
+        match edit {
+            Edit::Pin(row, col, val) => self.pin(row, col, val),
+            Edit::Unpin(row, col) => self.unpin(row, col),
+        }
     }
 
     /// Create an iterator through all the solutions of the [`Puzzle`].
@@ -186,14 +426,160 @@ impl Puzzle {
     }
 
     /// Compute the set of values in each cell that lead to a solvable grid.
-    pub fn possible_values(&self) -> Grid<BTreeSet<u8>> {
-        PossibleValuesFinder::new().search(self)
+    pub fn possible_values(&self) -> Grid<CandidateSet> {
+        PossibleValuesFinder::new()
+            .search(self)
+            .completed()
+            .expect("search without handle nor budget always completes")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Cage, Killer};
+
+    /// Whether a grid is a valid solution for a given geometry.
+    fn is_valid_solution(geometry: Geometry, sol: &Grid<u8>) -> bool {
+        let size = geometry.size();
+        let full = |cells: Vec<u8>| {
+            let mut cells = cells;
+            cells.sort();
+            cells == (1..=size as u8).collect::<Vec<_>>()
+        };
+        (0..size).all(|i| {
+            let row = (0..size).map(|c| sol[i][c]).collect();
+            let col = (0..size).map(|r| sol[r][i]).collect();
+            let boxed = sol
+                .cells()
+                .filter(|((r, c), _)| geometry.box_index(*r, *c) == i)
+                .map(|(_, &v)| v)
+                .collect();
+            full(row) && full(col) && full(boxed)
+        })
+    }
+
+    #[test]
+    fn all_sizes_solvable() {
+        for (box_rows, box_cols) in [(2, 2), (2, 3), (3, 2), (3, 4), (4, 4), (5, 5)] {
+            let geometry = Geometry::new(box_rows, box_cols);
+            let sol = Puzzle::new(geometry).solutions().next().unwrap();
+            assert_eq!(sol.size(), geometry.size());
+            assert!(is_valid_solution(geometry, &sol), "{box_rows}x{box_cols}");
+        }
+    }
+
+    #[test]
+    fn puzzle_4x4_single_sol() {
+        let p = Puzzle::from_arr([[0, 0, 0, 3], [0, 4, 0, 0], [0, 0, 3, 0], [2, 0, 0, 0]]);
+        let mut sols = p.solutions();
+        let expected = [[1, 2, 4, 3], [3, 4, 2, 1], [4, 1, 3, 2], [2, 3, 1, 4]];
+        let sol = sols.next().unwrap();
+        assert!(is_valid_solution(p.geometry(), &sol));
+        assert_eq!(sol, expected);
+        assert!(sols.next().is_none());
+    }
+
+    #[test]
+    fn puzzle_6x6_rectangular_boxes() {
+        let p = Puzzle::from_arr([
+            [0, 0, 0, 0, 4, 0],
+            [5, 6, 0, 0, 0, 0],
+            [0, 2, 0, 0, 0, 1],
+            [0, 0, 0, 3, 0, 0],
+            [0, 0, 1, 0, 0, 0],
+            [0, 0, 0, 0, 6, 5],
+        ]);
+        assert_eq!((p.geometry().box_rows(), p.geometry().box_cols()), (2, 3));
+        for sol in p.solutions().take(10) {
+            assert!(is_valid_solution(p.geometry(), &sol));
+        }
+    }
+
+    fn jigsaw_puzzle() -> Puzzle {
+        let regions = Regions::new([
+            [0, 0, 0, 1, 1, 1, 2, 2, 2],
+            [0, 0, 1, 1, 1, 2, 2, 2, 2],
+            [0, 0, 0, 1, 1, 1, 2, 2, 5],
+            [3, 0, 4, 4, 4, 4, 5, 5, 5],
+            [3, 3, 3, 3, 4, 4, 4, 5, 5],
+            [3, 3, 3, 4, 4, 7, 5, 5, 5],
+            [3, 6, 6, 6, 7, 7, 7, 8, 8],
+            [6, 6, 6, 7, 7, 7, 8, 8, 8],
+            [6, 6, 6, 7, 7, 8, 8, 8, 8],
+        ])
+        .unwrap();
+        Puzzle::from_arr([
+            [0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0, 0, 4, 0, 0],
+            [5, 0, 0, 0, 9, 0, 0, 3, 4],
+            [0, 0, 0, 0, 0, 0, 9, 0, 0],
+            [6, 0, 0, 0, 0, 0, 0, 1, 0],
+            [0, 1, 8, 0, 0, 4, 0, 0, 5],
+            [0, 0, 0, 0, 0, 0, 5, 0, 0],
+            [0, 0, 0, 0, 8, 0, 0, 0, 2],
+            [0, 0, 2, 0, 0, 0, 8, 0, 0],
+        ])
+        .with_regions(regions)
+    }
+
+    #[test]
+    fn jigsaw_single_sol() {
+        let p = jigsaw_puzzle();
+        let mut sols = p.solutions();
+        let expected = [
+            [1, 2, 3, 4, 5, 6, 7, 8, 9],
+            [8, 9, 1, 3, 7, 2, 4, 5, 6],
+            [5, 6, 7, 2, 9, 8, 1, 3, 4],
+            [2, 4, 5, 8, 1, 3, 9, 6, 7],
+            [6, 3, 9, 5, 4, 7, 2, 1, 8],
+            [7, 1, 8, 9, 6, 4, 3, 2, 5],
+            [4, 8, 6, 1, 2, 9, 5, 7, 3],
+            [3, 5, 4, 7, 8, 1, 6, 9, 2],
+            [9, 7, 2, 6, 3, 5, 8, 4, 1],
+        ];
+        let sol = sols.next().unwrap();
+        assert_eq!(sol, expected);
+        assert!(sols.next().is_none());
+        for ((row, col), vals) in p.possible_values().cells() {
+            assert_eq!(*vals, CandidateSet::from([sol[row][col]]));
+        }
+    }
+
+    #[test]
+    fn jigsaw_regions_replace_boxes() {
+        let p = jigsaw_puzzle();
+        assert_eq!(p.regions().region(3, 1), 0);
+        assert_eq!(Puzzle::default().regions(), &Regions::default());
+        // The top-left corner of the jigsaw solution breaks the classic box.
+        let mut classic = p.with_regions(Regions::default());
+        classic.pin(0, 0, Value::new(1));
+        classic.pin(1, 0, Value::new(8));
+        classic.pin(1, 1, Value::new(9));
+        assert!(classic.solutions().next().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn regions_of_other_size() {
+        let regions = Regions::boxes(Geometry::new(2, 2));
+        let _ = Puzzle::default().with_regions(regions);
+    }
+
+    #[test]
+    #[should_panic]
+    fn constraint_of_other_size() {
+        let cages = vec![Cage::new(3, vec![(0, 0), (0, 1)])];
+        let killer = Killer::new(4, cages).unwrap();
+        let _ = Puzzle::default().with_constraint(killer);
+    }
+
+    #[test]
+    #[should_panic]
+    fn value_too_large_for_grid() {
+        let mut p = Puzzle::new(Geometry::new(2, 2));
+        p.pin(0, 0, Value::new(5));
+    }
 
     #[test]
     fn direct_conflict_no_sol() {
@@ -0,0 +1,840 @@
+use std::fmt;
+
+use crate::constraint::Constraint;
+use crate::soft::SoftConstraint;
+use crate::{Grid, Puzzle};
+
+/// Techniques of the logical solver used by [`Puzzle::rate`], from the
+/// easiest to the hardest.
+///
+/// The techniques from [`Technique::UniqueRectangle`] to
+/// [`Technique::BugPlusOne`] rely on the puzzle having a single solution, and
+/// are only used by [`Puzzle::rate_with_uniqueness`].
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Technique {
+    /// A cell has a single candidate left.
+    NakedSingle,
+    /// A value has a single cell left in a row, column, or region.
+    HiddenSingle,
+    /// The cells of a region holding a value are all in one row or column, or
+    /// the other way around, so the value is removed from the rest of it.
+    LockedCandidates,
+    /// Some cells of a unit have as many candidates in total as there are
+    /// cells, which are removed from the other cells of the unit.
+    NakedSubset,
+    /// Some values of a unit have as many cells in total as there are values,
+    /// whose other candidates are removed.
+    HiddenSubset,
+    /// Unique rectangle of a given type, from 1 to 6: four cells on two rows,
+    /// two columns, and two regions must not be left with the same two
+    /// candidates, as they could then be swapped.
+    UniqueRectangle(u8),
+    /// Four cells on two rows, two columns, and two regions must not end up
+    /// with swappable values, when some of them are solved but not given.
+    AvoidableRectangle,
+    /// All the open cells but one have two candidates: the last one holds
+    /// the value that would otherwise appear three times in one of its units.
+    BugPlusOne,
+    /// None of the above applies, and a value has to be guessed.
+    Guess,
+}
+
+impl Technique {
+    /// Whether the technique is only valid for puzzles with a single
+    /// solution.
+    pub fn assumes_uniqueness(self) -> bool {
+        matches!(
+            self,
+            Technique::UniqueRectangle(_) | Technique::AvoidableRectangle | Technique::BugPlusOne
+        )
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Technique::NakedSingle => "naked-single",
+            Technique::HiddenSingle => "hidden-single",
+            Technique::LockedCandidates => "locked-candidates",
+            Technique::NakedSubset => "naked-subset",
+            Technique::HiddenSubset => "hidden-subset",
+            Technique::UniqueRectangle(kind) => return write!(f, "unique-rectangle-{kind}"),
+            Technique::AvoidableRectangle => "avoidable-rectangle",
+            Technique::BugPlusOne => "bug-plus-one",
+            Technique::Guess => "guess",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Difficulty of a puzzle, given by the techniques a logical solver applies
+/// to solve it, see [`Puzzle::rate`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Rating {
+    steps: Vec<Technique>,
+}
+
+impl Rating {
+    /// The hardest technique needed, [`Technique::NakedSingle`] for a puzzle
+    /// that is already solved.
+    pub fn hardest(&self) -> Technique {
+        self.steps
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(Technique::NakedSingle)
+    }
+
+    /// Number of times a technique is applied.
+    pub fn uses(&self, technique: Technique) -> usize {
+        self.steps.iter().filter(|&&t| t == technique).count()
+    }
+
+    /// The techniques applied, in order, each placing values or removing
+    /// candidates.
+    pub fn steps(&self) -> &[Technique] {
+        &self.steps
+    }
+
+    /// Whether a technique relying on the puzzle having a single solution
+    /// was applied.
+    pub fn assumes_uniqueness(&self) -> bool {
+        self.steps.iter().any(|t| t.assumes_uniqueness())
+    }
+}
+
+/// Largest subsets looked for by [`Technique::NakedSubset`] and
+/// [`Technique::HiddenSubset`].
+const MAX_SUBSET: usize = 4;
+
+/// Candidates of the cells, as bitmasks, during logical solving.  Cells are
+/// numbered in reading order.
+struct Logic {
+    size: usize,
+    candidates: Vec<u32>,
+    placed: Vec<bool>,
+    given: Vec<bool>,
+    /// Cells of the rows, columns, and regions, in this order.
+    units: Vec<Vec<usize>>,
+    /// Cells sharing a unit with each cell.
+    peers: Vec<Vec<usize>>,
+    /// Cells on two rows and two columns that lie in two regions, in reading
+    /// order.
+    rectangles: Vec<[usize; 4]>,
+}
+
+impl Logic {
+    fn new(puzzle: &Puzzle) -> Self {
+        let size = puzzle.geometry().size();
+        let units: Vec<Vec<usize>> = crate::LatinSquare
+            .groups(size)
+            .into_iter()
+            .chain(puzzle.regions().groups(size))
+            .map(|unit| unit.into_iter().map(|(r, c)| r * size + c).collect())
+            .collect();
+        let mut peers = vec![Vec::new(); size * size];
+        for unit in &units {
+            for &cell in unit {
+                peers[cell].extend(unit.iter().filter(|&&other| other != cell));
+            }
+        }
+        for cell_peers in &mut peers {
+            cell_peers.sort();
+            cell_peers.dedup();
+        }
+        let pairs: Vec<(usize, usize)> = (0..size)
+            .flat_map(|a| (a + 1..size).map(move |b| (a, b)))
+            .collect();
+        let regions = puzzle.regions();
+        let rectangles = pairs
+            .iter()
+            .flat_map(|&(r1, r2)| pairs.iter().map(move |&(c1, c2)| (r1, r2, c1, c2)))
+            .filter_map(|(r1, r2, c1, c2)| {
+                let cells = [(r1, c1), (r1, c2), (r2, c1), (r2, c2)];
+                let mut ids = cells.map(|(r, c)| regions.region(r, c));
+                ids.sort();
+                let two = ids[0] == ids[1] && ids[2] == ids[3] && ids[1] != ids[2];
+                two.then(|| cells.map(|(r, c)| r * size + c))
+            })
+            .collect();
+        let mut logic = Self {
+            size,
+            candidates: vec![(1 << size) - 1; size * size],
+            placed: vec![false; size * size],
+            given: vec![false; size * size],
+            units,
+            peers,
+            rectangles,
+        };
+        for cell in 0..size * size {
+            if let Some(val) = puzzle.get(cell / size, cell % size) {
+                logic.place(cell, 1 << (val.value() - 1));
+                logic.given[cell] = true;
+            }
+        }
+        logic
+    }
+
+    fn place(&mut self, cell: usize, bit: u32) {
+        self.candidates[cell] = bit;
+        self.placed[cell] = true;
+        for i in 0..self.peers[cell].len() {
+            self.candidates[self.peers[cell][i]] &= !bit;
+        }
+    }
+
+    /// Remove candidates from a cell, returning whether any were there.
+    fn eliminate(&mut self, cell: usize, bits: u32) -> bool {
+        let removed = self.candidates[cell] & bits != 0;
+        self.candidates[cell] &= !bits;
+        removed
+    }
+
+    fn naked_single(&mut self) -> bool {
+        let found = (0..self.candidates.len())
+            .find(|&cell| !self.placed[cell] && self.candidates[cell].count_ones() == 1);
+        if let Some(cell) = found {
+            self.place(cell, self.candidates[cell]);
+        }
+        found.is_some()
+    }
+
+    fn hidden_single(&mut self) -> bool {
+        for unit in 0..self.units.len() {
+            let mut once = 0;
+            let mut several = 0;
+            for &cell in &self.units[unit] {
+                several |= once & self.candidates[cell];
+                once |= self.candidates[cell];
+            }
+            let single = once & !several;
+            let found = self.units[unit]
+                .iter()
+                .copied()
+                .find(|&cell| !self.placed[cell] && self.candidates[cell] & single != 0);
+            if let Some(cell) = found {
+                let bit = self.candidates[cell] & single;
+                self.place(cell, bit & bit.wrapping_neg());
+                return true;
+            }
+        }
+        false
+    }
+
+    fn locked_candidates(&mut self) -> bool {
+        let mut changed = false;
+        for a in 0..self.units.len() {
+            for b in 0..self.units.len() {
+                let (unit_a, unit_b) = (&self.units[a], &self.units[b]);
+                if a == b || unit_a.iter().filter(|c| unit_b.contains(c)).count() < 2 {
+                    continue;
+                }
+                // Values of `a` only in cells shared with `b`.
+                let inside = unit_a
+                    .iter()
+                    .filter(|c| unit_b.contains(c))
+                    .fold(0, |acc, &c| acc | self.candidates[c]);
+                let outside = unit_a
+                    .iter()
+                    .filter(|c| !unit_b.contains(c))
+                    .fold(0, |acc, &c| acc | self.candidates[c]);
+                let locked = inside & !outside;
+                if locked == 0 {
+                    continue;
+                }
+                let others: Vec<usize> = unit_b
+                    .iter()
+                    .copied()
+                    .filter(|c| !unit_a.contains(c))
+                    .collect();
+                for cell in others {
+                    changed |= self.eliminate(cell, locked);
+                }
+            }
+        }
+        changed
+    }
+
+    fn naked_subset(&mut self) -> bool {
+        let mut changed = false;
+        for unit in self.units.clone() {
+            let open: Vec<usize> = unit.iter().copied().filter(|&c| !self.placed[c]).collect();
+            for k in 2..=MAX_SUBSET.min(open.len().saturating_sub(1)) {
+                for subset in subsets(&open, k) {
+                    let union = subset.iter().fold(0, |acc, &c| acc | self.candidates[c]);
+                    if union.count_ones() as usize != k {
+                        continue;
+                    }
+                    for &cell in open.iter().filter(|c| !subset.contains(c)) {
+                        changed |= self.eliminate(cell, union);
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    fn hidden_subset(&mut self) -> bool {
+        let mut changed = false;
+        for unit in self.units.clone() {
+            let open: Vec<usize> = unit.iter().copied().filter(|&c| !self.placed[c]).collect();
+            let values = open.iter().fold(0, |acc, &c| acc | self.candidates[c]);
+            let bits: Vec<usize> = (0..32).filter(|&v| values & (1 << v) != 0).collect();
+            for k in 2..=MAX_SUBSET.min(open.len().saturating_sub(1)) {
+                for subset in subsets(&bits, k) {
+                    let mask = subset.iter().fold(0, |acc, &v| acc | (1 << v));
+                    let cells: Vec<usize> = open
+                        .iter()
+                        .copied()
+                        .filter(|&c| self.candidates[c] & mask != 0)
+                        .collect();
+                    if cells.len() != k {
+                        continue;
+                    }
+                    for cell in cells {
+                        changed |= self.eliminate(cell, !mask);
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    fn sees(&self, a: usize, b: usize) -> bool {
+        self.peers[a].binary_search(&b).is_ok()
+    }
+
+    /// Remove candidates from the open cells that see all the given cells.
+    fn eliminate_seen(&mut self, cells: &[usize], bits: u32) -> bool {
+        let mut changed = false;
+        for cell in 0..self.candidates.len() {
+            if !self.placed[cell] && cells.iter().all(|&c| c != cell && self.sees(c, cell)) {
+                changed |= self.eliminate(cell, bits);
+            }
+        }
+        changed
+    }
+
+    /// The units containing all the given cells.
+    fn common_units(&self, cells: &[usize]) -> Vec<usize> {
+        (0..self.units.len())
+            .filter(|&u| cells.iter().all(|c| self.units[u].contains(c)))
+            .collect()
+    }
+
+    /// Apply the first unique rectangle found, returning its type.
+    fn unique_rectangle(&mut self) -> Option<u8> {
+        for i in 0..self.rectangles.len() {
+            let rect = self.rectangles[i];
+            if rect.iter().any(|&c| self.placed[c]) {
+                continue;
+            }
+            let common = rect.iter().fold(!0, |acc, &c| acc & self.candidates[c]);
+            let bits: Vec<usize> = (0..self.size).filter(|&v| common & (1 << v) != 0).collect();
+            for pair in subsets(&bits, 2) {
+                let pair = (1 << pair[0]) | (1 << pair[1]);
+                if let Some(kind) = self.unique_rectangle_on(rect, pair) {
+                    return Some(kind);
+                }
+            }
+        }
+        None
+    }
+
+    /// Apply a unique rectangle on given cells, in reading order, with a
+    /// given pair of candidates in all of them.
+    fn unique_rectangle_on(&mut self, rect: [usize; 4], pair: u32) -> Option<u8> {
+        let roofs: Vec<usize> = rect
+            .iter()
+            .copied()
+            .filter(|&c| self.candidates[c] != pair)
+            .collect();
+        let extra = roofs
+            .iter()
+            .fold(0, |acc, &c| acc | self.candidates[c] & !pair);
+        // Cells on a side of the rectangle share a row or a column.
+        let side = roofs.len() == 2
+            && (roofs[0] / self.size == roofs[1] / self.size
+                || roofs[0] % self.size == roofs[1] % self.size);
+        if roofs.len() == 1 {
+            self.eliminate(roofs[0], pair);
+            return Some(1);
+        }
+        // With a single extra value, one of the roofs holds it.
+        let same_extra =
+            extra.count_ones() == 1 && roofs.iter().all(|&c| self.candidates[c] == pair | extra);
+        if same_extra && self.eliminate_seen(&roofs, extra) {
+            return Some(if side { 2 } else { 5 });
+        }
+        if side {
+            for unit in self.common_units(&roofs) {
+                // The roofs together act as a single cell with the extra
+                // values, in a naked subset.
+                let others: Vec<usize> = self.units[unit]
+                    .iter()
+                    .copied()
+                    .filter(|&c| !self.placed[c] && !roofs.contains(&c))
+                    .collect();
+                for n in 1..MAX_SUBSET.min(others.len() + 1) {
+                    for subset in subsets(&others, n) {
+                        let union = subset
+                            .iter()
+                            .fold(extra, |acc, &c| acc | self.candidates[c]);
+                        if union.count_ones() as usize != n + 1 {
+                            continue;
+                        }
+                        let mut changed = false;
+                        for &cell in others.iter().filter(|c| !subset.contains(c)) {
+                            changed |= self.eliminate(cell, union);
+                        }
+                        if changed {
+                            return Some(3);
+                        }
+                    }
+                }
+                // A value of the pair only in the roofs rules out the other.
+                for bit in [pair & pair.wrapping_neg(), pair & (pair - 1)] {
+                    let only_roofs = self.units[unit]
+                        .iter()
+                        .all(|c| roofs.contains(c) || self.candidates[*c] & bit == 0);
+                    if only_roofs
+                        && roofs
+                            .iter()
+                            .fold(false, |acc, &c| self.eliminate(c, pair & !bit) | acc)
+                    {
+                        return Some(4);
+                    }
+                }
+            }
+        } else if roofs.len() == 2 {
+            // A value of the pair only in the rectangle in both rows cannot
+            // be in the roofs, on a diagonal, without a swappable pattern.
+            let rows = [rect[0] / self.size, rect[3] / self.size];
+            for bit in [pair & pair.wrapping_neg(), pair & (pair - 1)] {
+                let locked = rows.iter().all(|&row| {
+                    self.units[row]
+                        .iter()
+                        .all(|c| rect.contains(c) || self.candidates[*c] & bit == 0)
+                });
+                if locked
+                    && roofs
+                        .iter()
+                        .fold(false, |acc, &c| self.eliminate(c, bit) | acc)
+                {
+                    return Some(6);
+                }
+            }
+        }
+        None
+    }
+
+    /// Avoid rectangles whose solved cells are not givens, and could be
+    /// swapped with the open ones.
+    fn avoidable_rectangle(&mut self) -> bool {
+        for i in 0..self.rectangles.len() {
+            let rect = self.rectangles[i];
+            if rect.iter().any(|&c| self.given[c]) {
+                continue;
+            }
+            // Opposite corners of the rectangle: 0 and 3, 1 and 2.
+            let open: Vec<usize> = (0..4).filter(|&k| !self.placed[rect[k]]).collect();
+            match open[..] {
+                [k] => {
+                    let opposite = self.candidates[rect[3 - k]];
+                    let adjacent = [rect[k ^ 1], rect[k ^ 2]].map(|c| self.candidates[c]);
+                    if adjacent[0] == adjacent[1]
+                        && adjacent[0] != opposite
+                        && self.eliminate(rect[k], opposite)
+                    {
+                        return true;
+                    }
+                }
+                [k, l] if k ^ l != 3 => {
+                    // Each open cell must not take the value of the solved
+                    // cell diagonal to it, and holds it or one extra value.
+                    let needed_k = self.candidates[rect[3 - k]];
+                    let needed_l = self.candidates[rect[3 - l]];
+                    let extra_k = self.candidates[rect[k]] & !needed_k;
+                    let extra_l = self.candidates[rect[l]] & !needed_l;
+                    let (cell_k, cell_l) = (rect[k], rect[l]);
+                    if self.candidates[cell_k].count_ones() == 2
+                        && self.candidates[cell_k] & needed_k != 0
+                        && extra_k == extra_l
+                        && self.candidates[cell_l] == needed_l | extra_l
+                        && self.eliminate_seen(&[cell_k, cell_l], extra_k)
+                    {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Place the value of the only open cell with three candidates, when all
+    /// the others have two, that leaves each candidate of each unit in two
+    /// cells.
+    fn bug_plus_one(&mut self) -> bool {
+        let open: Vec<usize> = (0..self.candidates.len())
+            .filter(|&c| !self.placed[c])
+            .collect();
+        let mut large = open
+            .iter()
+            .copied()
+            .filter(|&c| self.candidates[c].count_ones() != 2);
+        let (Some(cell), None) = (large.next(), large.next()) else {
+            return false;
+        };
+        if self.candidates[cell].count_ones() != 3 {
+            return false;
+        }
+        let found = (0..self.size).map(|v| 1 << v).find(|&bit| {
+            self.candidates[cell] & bit != 0
+                && self.units.iter().all(|unit| {
+                    (0..self.size).all(|v| {
+                        let count = unit
+                            .iter()
+                            .filter(|&&c| {
+                                let candidates = if c == cell {
+                                    self.candidates[c] & !bit
+                                } else {
+                                    self.candidates[c]
+                                };
+                                !self.placed[c] && candidates & (1 << v) != 0
+                            })
+                            .count();
+                        count == 0 || count == 2
+                    })
+                })
+        });
+        if let Some(bit) = found {
+            self.place(cell, bit);
+        }
+        found.is_some()
+    }
+
+    /// Place the value of a solution in the open cell with the fewest
+    /// candidates.
+    fn guess(&mut self, solution: &Grid<u8>) {
+        let size = solution.size();
+        let cell = (0..self.candidates.len())
+            .filter(|&cell| !self.placed[cell])
+            .min_by_key(|&cell| self.candidates[cell].count_ones())
+            .expect("the grid is not solved yet");
+        self.place(cell, 1 << (solution[cell / size][cell % size] - 1));
+    }
+
+    /// Apply the easiest technique that makes progress, if any, without
+    /// guessing.
+    fn deduce(&mut self, uniqueness: bool) -> Option<Technique> {
+        if self.naked_single() {
+            Some(Technique::NakedSingle)
+        } else if self.hidden_single() {
+            Some(Technique::HiddenSingle)
+        } else if self.locked_candidates() {
+            Some(Technique::LockedCandidates)
+        } else if self.naked_subset() {
+            Some(Technique::NakedSubset)
+        } else if self.hidden_subset() {
+            Some(Technique::HiddenSubset)
+        } else if let Some(kind) = uniqueness.then(|| self.unique_rectangle()).flatten() {
+            Some(Technique::UniqueRectangle(kind))
+        } else if uniqueness && self.avoidable_rectangle() {
+            Some(Technique::AvoidableRectangle)
+        } else if uniqueness && self.bug_plus_one() {
+            Some(Technique::BugPlusOne)
+        } else {
+            None
+        }
+    }
+
+    /// Apply the easiest technique that makes progress, guessing with the
+    /// value of the solution if none does.
+    fn step(&mut self, uniqueness: bool, solution: &Grid<u8>) -> Technique {
+        self.deduce(uniqueness).unwrap_or_else(|| {
+            self.guess(solution);
+            Technique::Guess
+        })
+    }
+
+    fn is_solved(&self) -> bool {
+        self.placed.iter().all(|&p| p)
+    }
+}
+
+/// Apply to the candidates of a puzzle the easiest technique that removes
+/// some, see [`crate::CandidateGrid::apply_hint`].
+pub(crate) fn hint(puzzle: &Puzzle, candidates: &mut Grid<SoftConstraint>) -> Option<Technique> {
+    let size = candidates.size();
+    if candidates.cells().any(|(_, sc)| !sc.has_solutions()) {
+        return None;
+    }
+    let mut logic = Logic::new(puzzle);
+    for ((row, col), sc) in candidates.cells() {
+        logic.eliminate(row * size + col, !sc.bits());
+    }
+    // Cells with a single candidate are filled.
+    for cell in 0..size * size {
+        let bits = candidates[cell / size][cell % size].bits();
+        if !logic.placed[cell] && bits.count_ones() == 1 {
+            logic.place(cell, bits);
+        }
+    }
+    let technique = if changes(&logic, candidates) {
+        Technique::NakedSingle
+    } else {
+        loop {
+            let technique = logic.deduce(false)?;
+            if changes(&logic, candidates) {
+                break technique;
+            }
+        }
+    };
+    *candidates = Grid::from_fn(size, |row, col| {
+        SoftConstraint::from_bits(logic.candidates[row * size + col]).expect("values are in range")
+    });
+    Some(technique)
+}
+
+/// Whether the candidates of the logical solver differ from `candidates`.
+fn changes(logic: &Logic, candidates: &Grid<SoftConstraint>) -> bool {
+    let size = candidates.size();
+    candidates
+        .cells()
+        .any(|((row, col), sc)| logic.candidates[row * size + col] != sc.bits())
+}
+
+/// All the subsets of `k` items, in lexicographic order.
+fn subsets(items: &[usize], k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    (0..items.len())
+        .rev()
+        .skip(k - 1)
+        .rev()
+        .flat_map(|i| {
+            subsets(&items[i + 1..], k - 1)
+                .into_iter()
+                .map(move |mut rest| {
+                    rest.insert(0, items[i]);
+                    rest
+                })
+        })
+        .collect()
+}
+
+impl Puzzle {
+    /// Rate the difficulty of a puzzle with a logical solver, which applies
+    /// the easiest [`Technique`] that makes progress until the grid is
+    /// solved.  This is `None` if the puzzle does not have a single solution.
+    ///
+    /// Only rows, columns, and regions are considered: puzzles relying on
+    /// other constraints need guesses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, Technique};
+    ///
+    /// let pzl = Puzzle::from_line(
+    ///     "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    /// ).unwrap();
+    /// let rating = pzl.rate().unwrap();
+    /// assert!(rating.hardest() <= Technique::HiddenSingle);
+    /// assert_eq!(rating.uses(Technique::Guess), 0);
+    /// ```
+    pub fn rate(&self) -> Option<Rating> {
+        self.rate_logically(false)
+    }
+
+    /// Rate the difficulty of a puzzle like [`Self::rate`], also using the
+    /// techniques that rely on the puzzle having a single solution, see
+    /// [`Technique::assumes_uniqueness`].
+    ///
+    /// Those techniques are left out for puzzles with constraints added by
+    /// [`Self::with_constraint`]: they rule out values that would leave two
+    /// solutions differing by a swap of values, which a killer cage or a
+    /// thermometer may tell apart.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, Technique};
+    ///
+    /// let pzl = Puzzle::from_line(
+    ///     "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    /// ).unwrap();
+    /// let rating = pzl.rate_with_uniqueness().unwrap();
+    /// // Easier techniques are always tried first.
+    /// assert!(!rating.assumes_uniqueness());
+    /// ```
+    pub fn rate_with_uniqueness(&self) -> Option<Rating> {
+        self.rate_logically(true)
+    }
+
+    fn rate_logically(&self, uniqueness: bool) -> Option<Rating> {
+        let mut sols = self.solutions();
+        let solution = sols.next()?;
+        if sols.next().is_some() {
+            return None;
+        }
+        let uniqueness = uniqueness && self.added_constraints().next().is_none();
+        let mut logic = Logic::new(self);
+        let mut steps = Vec::new();
+        while !logic.is_solved() {
+            steps.push(logic.step(uniqueness, &solution));
+        }
+        Some(Rating { steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::classic_puzzle;
+    use crate::{Cage, Killer};
+
+    #[test]
+    fn subsets_of_items() {
+        assert_eq!(subsets(&[1, 2, 3], 2), [[1, 2], [1, 3], [2, 3]]);
+        assert_eq!(subsets(&[1, 2, 3], 3), [[1, 2, 3]]);
+        assert!(subsets(&[1, 2], 3).is_empty());
+    }
+
+    #[test]
+    fn locked_candidates_in_a_box() {
+        let mut logic = Logic::new(&Puzzle::default());
+        // Remove 1 from the first box, except in its first row.
+        for cell in [9, 10, 11, 18, 19, 20] {
+            logic.eliminate(cell, 1);
+        }
+        assert!(logic.locked_candidates());
+        assert!((3..9).all(|cell| logic.candidates[cell] & 1 == 0));
+        assert!((0..3).all(|cell| logic.candidates[cell] & 1 == 1));
+        assert_eq!(logic.candidates[12] & 1, 1);
+    }
+
+    #[test]
+    fn naked_and_hidden_pairs() {
+        let mut logic = Logic::new(&Puzzle::default());
+        logic.candidates[0] = 0b11;
+        logic.candidates[1] = 0b11;
+        assert!(logic.naked_subset());
+        assert!((2..9).all(|cell| logic.candidates[cell] & 0b11 == 0));
+        assert!(!logic.naked_subset());
+
+        let mut logic = Logic::new(&Puzzle::default());
+        // 1 and 2 are only in the first two cells of the first row.
+        for cell in 2..9 {
+            logic.eliminate(cell, 0b11);
+        }
+        assert!(logic.hidden_subset());
+        assert_eq!((logic.candidates[0], logic.candidates[1]), (0b11, 0b11));
+    }
+
+    #[test]
+    fn ratings() {
+        let easy = classic_puzzle();
+        let rating = easy.rate().unwrap();
+        assert!(rating.hardest() <= Technique::HiddenSingle);
+        assert_eq!(rating.steps().len(), 81 - 30);
+        let hard = Puzzle::from_line(
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+        )
+        .unwrap();
+        assert!(hard.rate().unwrap().hardest() > Technique::HiddenSingle);
+        assert_eq!(Puzzle::default().rate(), None);
+        let solved = easy.solutions().next().unwrap();
+        let solved = Puzzle::from_grid(crate::Geometry::CLASSIC, solved);
+        assert_eq!(solved.rate().unwrap().hardest(), Technique::NakedSingle);
+        assert_eq!(Technique::LockedCandidates.to_string(), "locked-candidates");
+    }
+
+    #[test]
+    fn unique_rectangle_type_1() {
+        let mut logic = Logic::new(&Puzzle::default());
+        // r1c1, r1c4, r2c1 can only be 1 or 2, r2c4 can also be 3.
+        for cell in [0, 3, 9] {
+            logic.candidates[cell] = 0b11;
+        }
+        logic.candidates[12] = 0b111;
+        assert_eq!(logic.unique_rectangle(), Some(1));
+        assert_eq!(logic.candidates[12], 0b100);
+    }
+
+    #[test]
+    fn uniqueness_techniques_are_sound() {
+        let puzzles = [
+            (
+                "...58..4......273.4...6...1...8...676...3.1....8...3..7...5....8.9.......4672...8",
+                Technique::AvoidableRectangle,
+            ),
+            (
+                ".8....2........54..23...1.8.....8.6..3..5.......462....9...5.2.47.9........3.64..",
+                Technique::BugPlusOne,
+            ),
+            (
+                "..3....1.7...3.....8....6...9...8..682..7.9....1...8.4.....65......1...29.2..43.8",
+                Technique::UniqueRectangle(1),
+            ),
+            (
+                "7.1.........9..5...4.31.....3..8...48.....6.312...3...........5.13..4..75..7..3.9",
+                Technique::UniqueRectangle(2),
+            ),
+            (
+                "...7...9....8....3138......9..2.3........7.5...6...94....49.......5...242.....6..",
+                Technique::UniqueRectangle(3),
+            ),
+            (
+                "..5.8.9.2.9....8...3.1.......25....8.19....2......61....8.72...37...5......34....",
+                Technique::UniqueRectangle(4),
+            ),
+            (
+                ".8...7..2.6..145....1..........76.....8...64.91.3...7..7.1...596...9............7",
+                Technique::UniqueRectangle(5),
+            ),
+            (
+                ".8....2........54..23...1.8.....8.6..3..5.......462....9...5.2.47.9........3.64..",
+                Technique::UniqueRectangle(6),
+            ),
+        ];
+        for (line, technique) in puzzles {
+            let pzl = Puzzle::from_line(line).unwrap();
+            let solution = pzl.solutions().next().unwrap();
+            let mut logic = Logic::new(&pzl);
+            let mut steps = Vec::new();
+            while !logic.is_solved() {
+                steps.push(logic.step(true, &solution));
+                for ((row, col), &val) in solution.cells() {
+                    let bit = 1 << (val - 1);
+                    assert_ne!(logic.candidates[row * 9 + col] & bit, 0, "{line}");
+                }
+            }
+            assert!(steps.contains(&technique), "{line}");
+            let rating = pzl.rate_with_uniqueness().unwrap();
+            assert_eq!(rating.steps(), steps);
+            assert!(rating.assumes_uniqueness());
+            assert!(!pzl.rate().unwrap().assumes_uniqueness());
+        }
+        assert_eq!(
+            Technique::UniqueRectangle(3).to_string(),
+            "unique-rectangle-3"
+        );
+    }
+
+    #[test]
+    fn uniqueness_techniques_skip_variants() {
+        let line =
+            "..3....1.7...3.....8....6...9...8..682..7.9....1...8.4.....65......1...29.2..43.8";
+        let pzl = Puzzle::from_line(line).unwrap();
+        assert!(pzl.rate_with_uniqueness().unwrap().assumes_uniqueness());
+        let sol = pzl.solutions().next().unwrap();
+        let cage = Cage::new(u32::from(sol[0][0] + sol[0][1]), vec![(0, 0), (0, 1)]);
+        let killer = pzl.with_constraint(Killer::new(9, vec![cage]).unwrap());
+        let rating = killer.rate_with_uniqueness().unwrap();
+        assert!(!rating.assumes_uniqueness());
+        assert_eq!(rating, killer.rate().unwrap());
+    }
+}
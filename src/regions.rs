@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::constraint::Constraint;
+use crate::grid::{Geometry, Grid, MAX_SIZE};
+
+/// Partition of a grid into regions that must each contain all the values
+/// once, in addition to rows and columns.  Classic puzzles use the boxes of
+/// their [`Geometry`], jigsaw puzzles use arbitrary connected shapes.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::Regions;
+///
+/// let regions = Regions::new([
+///     [0, 0, 0, 1],
+///     [0, 2, 1, 1],
+///     [2, 2, 3, 1],
+///     [2, 3, 3, 3],
+/// ]).unwrap();
+/// assert_eq!(regions.size(), 4);
+/// assert_eq!(regions.region(3, 0), 2);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Regions {
+    map: Grid<usize>,
+}
+
+/// Error signaling that a region map given to [`Regions::new`] does not
+/// describe a valid partition of the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionError {
+    /// The grid is empty or larger than [`MAX_SIZE`].
+    UnsupportedSize(usize),
+    /// The region index at a given position is not smaller than the size of
+    /// the grid.
+    BadIndex(usize, usize),
+    /// A region does not have as many cells as the size of the grid.
+    WrongCellCount(usize),
+    /// The cells of a region are not connected.
+    Disconnected(usize),
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::UnsupportedSize(size) => write!(f, "unsupported grid size {size}"),
+            RegionError::BadIndex(row, col) => {
+                write!(f, "invalid region index at row {row}, column {col}")
+            }
+            RegionError::WrongCellCount(region) => {
+                write!(f, "region {region} has the wrong number of cells")
+            }
+            RegionError::Disconnected(region) => write!(f, "region {region} is not connected"),
+        }
+    }
+}
+
+impl std::error::Error for RegionError {}
+
+impl Regions {
+    /// Build regions from a map giving the 0-indexed region of each cell.
+    /// There must be as many regions as rows, each with as many cells as
+    /// rows, and the cells of a region must be connected through their
+    /// edges.
+    pub fn new<A: Into<Grid<usize>>>(map: A) -> Result<Self, RegionError> {
+        let map = map.into();
+        let size = map.size();
+        if size == 0 || size > MAX_SIZE {
+            return Err(RegionError::UnsupportedSize(size));
+        }
+        let mut counts = vec![0; size];
+        for ((row, col), &region) in map.cells() {
+            if region >= size {
+                return Err(RegionError::BadIndex(row, col));
+            }
+            counts[region] += 1;
+        }
+        if let Some(region) = counts.iter().position(|&c| c != size) {
+            return Err(RegionError::WrongCellCount(region));
+        }
+        let regions = Self { map };
+        if let Some(region) = (0..size).find(|&r| !regions.is_connected(r)) {
+            return Err(RegionError::Disconnected(region));
+        }
+        Ok(regions)
+    }
+
+    /// The boxes of a given [`Geometry`].
+    pub fn boxes(geometry: Geometry) -> Self {
+        Self {
+            map: Grid::from_fn(geometry.size(), |row, col| geometry.box_index(row, col)),
+        }
+    }
+
+    /// Number of rows, columns, and regions of the grid.
+    pub fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    /// Index of the region containing a given cell.  This is 0-indexed.
+    pub fn region(&self, row: usize, col: usize) -> usize {
+        self.map[row][col]
+    }
+
+    /// Flood fill a region from its first cell, and check it reaches all of
+    /// its cells.
+    fn is_connected(&self, region: usize) -> bool {
+        let size = self.size();
+        let mut cells = self.map.cells().filter(|(_, &r)| r == region);
+        let Some((start, _)) = cells.next() else {
+            return true;
+        };
+        let mut seen = Grid::filled(size, false);
+        seen[start.0][start.1] = true;
+        let mut stack = vec![start];
+        let mut reached = 1;
+        while let Some((row, col)) = stack.pop() {
+            let neighbours = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for (nrow, ncol) in neighbours {
+                if nrow < size && ncol < size && !seen[nrow][ncol] && self.map[nrow][ncol] == region
+                {
+                    seen[nrow][ncol] = true;
+                    reached += 1;
+                    stack.push((nrow, ncol));
+                }
+            }
+        }
+        reached == size
+    }
+}
+
+impl Constraint for Regions {
+    fn groups(&self, _size: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut groups = vec![Vec::new(); self.size()];
+        for ((row, col), &region) in self.map.cells() {
+            groups[region].push((row, col));
+        }
+        groups
+    }
+}
+
+impl Default for Regions {
+    /// The boxes of the classic 9x9 grid.
+    fn default() -> Self {
+        Self::boxes(Geometry::CLASSIC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxes_are_valid_regions() {
+        for geometry in [Geometry::new(2, 3), Geometry::CLASSIC, Geometry::new(4, 4)] {
+            let boxes = Regions::boxes(geometry);
+            let map = Grid::from_fn(geometry.size(), |row, col| boxes.region(row, col));
+            assert_eq!(Regions::new(map), Ok(boxes));
+        }
+    }
+
+    #[test]
+    fn invalid_region_maps() {
+        assert_eq!(
+            Regions::new(Grid::filled(0, 0)),
+            Err(RegionError::UnsupportedSize(0))
+        );
+        assert_eq!(
+            Regions::new([[0, 0, 1, 1], [0, 0, 1, 1], [2, 2, 3, 3], [2, 2, 3, 4]]),
+            Err(RegionError::BadIndex(3, 3))
+        );
+        assert_eq!(
+            Regions::new([[0, 0, 1, 1], [0, 0, 1, 1], [2, 2, 3, 3], [2, 2, 3, 2]]),
+            Err(RegionError::WrongCellCount(2))
+        );
+        assert_eq!(
+            Regions::new([[0, 0, 1, 1], [1, 1, 0, 0], [2, 2, 3, 3], [2, 2, 3, 3]]),
+            Err(RegionError::Disconnected(0))
+        );
+    }
+}
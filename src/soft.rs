@@ -1,22 +1,25 @@
 use std::collections::BTreeSet;
 
+use crate::grid::{Geometry, MAX_SIZE};
 use crate::puzzle::Value;
 
-const ALL_ON: u16 = (1 << 9) - 1;
+/// Bitmask with the `size` lowest bits set.
+pub(crate) const fn all_on(size: usize) -> u32 {
+    ((1u64 << size) - 1) as u32
+}
 
 #[derive(Copy, Clone)]
-pub struct SoftConstraint(u16);
+pub struct SoftConstraint(u32);
 
 impl SoftConstraint {
+    /// A constraint allowing all the values of a grid of a given size.
+    pub fn all(size: usize) -> Self {
+        SoftConstraint(all_on(size))
+    }
+
     pub fn unique_solution(&self) -> Option<Value> {
         if self.num_solutions() == 1 {
-            let mut i = 1;
-            let mut inner: u16 = self.0;
-            while inner != 1 {
-                i += 1;
-                inner /= 2;
-            }
-            Some(Value::new(i))
+            self.smallest_solution()
         } else {
             None
         }
@@ -24,18 +27,27 @@ impl SoftConstraint {
 
     pub fn smallest_solution(&self) -> Option<Value> {
         if self.0 != 0 {
-            let mut i = 1;
-            let mut inner: u16 = self.0;
-            while inner % 2 != 1 {
-                i += 1;
-                inner /= 2;
-            }
-            Some(Value::new(i))
+            Some(Value::new(self.0.trailing_zeros() as u8 + 1))
         } else {
             None
         }
     }
 
+    /// A constraint with no possible value.
+    pub fn empty() -> Self {
+        SoftConstraint(0)
+    }
+
+    /// Add a given value to the possible values.
+    pub fn allow(&mut self, val: Value) {
+        self.0 |= Self::from(val).0;
+    }
+
+    /// Possible values of `self` that are not possible values of `other`.
+    pub fn difference(&self, other: SoftConstraint) -> SoftConstraint {
+        SoftConstraint(self.0 & !other.0)
+    }
+
     pub fn has_solutions(&self) -> bool {
         self.0 != 0
     }
@@ -50,12 +62,23 @@ impl SoftConstraint {
 
     /// Remove a given value of the possible values.
     pub fn forbid(&mut self, val: Value) {
-        self.0 &= ALL_ON - Self::from(val).0;
+        self.0 &= !Self::from(val).0;
+    }
+
+    /// Raw bitmask of possible values, the lowest bit standing for 1.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Build from a raw bitmask as returned by [`Self::bits`], `None` if
+    /// bits beyond the [`MAX_SIZE`]-th are set.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        (bits & !all_on(MAX_SIZE) == 0).then_some(SoftConstraint(bits))
     }
 
     /// Iterate through possible values.
     pub fn all_values(&self) -> BTreeSet<Value> {
-        (1..=9)
+        (1..=MAX_SIZE as u8)
             .map(Value::new)
             .filter(|&v| self.has_solution(v))
             .collect()
@@ -63,8 +86,9 @@ impl SoftConstraint {
 }
 
 impl Default for SoftConstraint {
+    /// All the values of the classic 9x9 grid.
     fn default() -> Self {
-        SoftConstraint(ALL_ON)
+        SoftConstraint::all(Geometry::CLASSIC.size())
     }
 }
 
@@ -136,6 +160,32 @@ mod tests {
         assert_eq!(sc.num_solutions(), 9);
     }
 
+    #[test]
+    fn allow_and_difference() {
+        let mut sc = SoftConstraint::empty();
+        assert!(!sc.has_solutions());
+        sc.allow(Value::new(3));
+        sc.allow(Value::new(5));
+        assert_eq!(sc.num_solutions(), 2);
+        let diff = SoftConstraint::default().difference(sc);
+        assert_eq!(diff.num_solutions(), 7);
+        assert!(!diff.has_solution(Value::new(3)));
+        assert!(!diff.has_solution(Value::new(5)));
+        assert!(!sc.difference(SoftConstraint::default()).has_solutions());
+    }
+
+    #[test]
+    fn all_of_size() {
+        let sc = SoftConstraint::all(25);
+        assert_eq!(sc.num_solutions(), 25);
+        assert!(sc.has_solution(Value::new(25)));
+        assert_eq!(
+            SoftConstraint::from(Value::new(25)).unique_solution(),
+            Some(Value::new(25))
+        );
+        assert_eq!(SoftConstraint::all(4).all_values().len(), 4);
+    }
+
     #[test]
     fn forbid() {
         let mut sc = SoftConstraint::from(None);
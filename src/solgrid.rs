@@ -1,6 +1,9 @@
 use std::collections::BTreeSet;
+use std::sync::Arc;
 
-use crate::puzzle::{Grid, Puzzle, Value};
+use crate::constraint::{self, Constraint};
+use crate::grid::{Geometry, Grid};
+use crate::puzzle::{Puzzle, Value};
 use crate::soft::SoftConstraint;
 
 /// Some operations return this type wrapped in an error to signal that the
@@ -57,7 +60,7 @@ impl CellState {
             CellState::Pinned(v) if val != *v => Ok(()),
             CellState::Fuzzy(sc) => {
                 sc.forbid(val);
-                (sc.has_solutions()).then(|| ()).ok_or(NoSolError {})
+                (sc.has_solutions()).then_some(()).ok_or(NoSolError {})
             }
             _ => Err(NoSolError {}),
         }
@@ -75,66 +78,170 @@ impl CellState {
     }
 }
 
-impl Default for CellState {
-    fn default() -> Self {
-        CellState::Fuzzy(SoftConstraint::default())
+/// Constraints of a [`Puzzle`], with the cells sharing a group of distinct
+/// values with each cell and the groups covering a whole row, column, or
+/// region.  This is shared by all the [`SolutionGrid`] derived from the same
+/// [`Puzzle`].
+pub struct Layout {
+    geometry: Geometry,
+    constraints: Vec<Arc<dyn Constraint>>,
+    units: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    pub fn new(puzzle: &Puzzle) -> Self {
+        let geometry = puzzle.geometry();
+        let size = geometry.size();
+        let constraints = puzzle.shared_constraints();
+        let groups: Vec<Vec<usize>> = constraints
+            .iter()
+            .flat_map(|c| c.groups(size))
+            .map(|group| group.iter().map(|(row, col)| row * size + col).collect())
+            .collect();
+        let mut peers = vec![Vec::new(); geometry.n_cells()];
+        for group in &groups {
+            for &idx in group {
+                peers[idx].extend(group.iter().filter(|&&other| other != idx));
+            }
+        }
+        for cell_peers in &mut peers {
+            cell_peers.sort_unstable();
+            cell_peers.dedup();
+        }
+        let units = groups.into_iter().filter(|g| g.len() == size).collect();
+        Self {
+            geometry,
+            constraints,
+            units,
+            peers,
+        }
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
     }
 }
 
 /// A sudoku grid solution.
-#[derive(Clone, Default)]
-pub struct SolutionGrid([[CellState; 9]; 9]);
+#[derive(Clone)]
+pub struct SolutionGrid {
+    layout: Arc<Layout>,
+    cells: Vec<CellState>,
+}
 
 impl SolutionGrid {
+    /// A grid where all cells can take any value.
+    pub fn new(layout: Arc<Layout>) -> Self {
+        let size = layout.geometry.size();
+        let cells = vec![CellState::Fuzzy(SoftConstraint::all(size)); layout.geometry.n_cells()];
+        Self { layout, cells }
+    }
+
+    pub fn layout(&self) -> &Arc<Layout> {
+        &self.layout
+    }
+
+    /// Number of rows, columns, and values of the grid.
+    pub fn size(&self) -> usize {
+        self.layout.geometry.size()
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        row * self.size() + col
+    }
+
     pub fn is_solved(&self) -> bool {
-        self.0.iter().flatten().all(|cs| cs.is_fixed())
+        self.cells.iter().all(|cs| cs.is_fixed())
     }
 
     pub fn pin(&mut self, row: usize, col: usize, val: Value) -> SolResult<()> {
-        self.0[row][col].pin(val)?;
-        for i in 0..9 {
-            if i != row {
-                self.forbid(i, col, val)?;
-            }
-            if i != col {
-                self.forbid(row, i, val)?;
-            }
-        }
-        let block_row = row / 3;
-        let block_col = col / 3;
-        for ir in (3 * block_row)..(3 * (block_row + 1)) {
-            for ic in (3 * block_col)..(3 * (block_col + 1)) {
-                if ir != row && ic != col {
-                    self.forbid(ir, ic, val)?;
-                }
-            }
+        let idx = self.index(row, col);
+        self.cells[idx].pin(val)?;
+        let layout = self.layout.clone();
+        for &peer in &layout.peers[idx] {
+            self.cells[peer].forbid(val)?;
         }
         Ok(())
     }
 
     pub fn forbid(&mut self, row: usize, col: usize, val: Value) -> SolResult<()> {
-        self.0[row][col].forbid(val)
+        let idx = self.index(row, col);
+        self.cells[idx].forbid(val)
+    }
+
+    /// Forbid all the values that are not in `allowed`, and propagate.
+    pub fn restrict(&mut self, allowed: &Grid<SoftConstraint>) -> SolResult<()> {
+        for ((row, col), allowed) in allowed.cells() {
+            for val in self.candidates(row, col).difference(*allowed).all_values() {
+                self.forbid(row, col, val)?;
+            }
+        }
+        self.maximize_constraints()
     }
 
     /// Find all fuzzy cells with only one possibility left.
     fn find_fuzzy_uniques(&self) -> Vec<(usize, usize, Value)> {
+        let size = self.size();
         let mut out = Vec::new();
-        for row in 0..9 {
-            for col in 0..9 {
-                if let Some(sc) = self.0[row][col].fuzzy_constraint() {
-                    if let Some(val) = sc.unique_solution() {
-                        out.push((row, col, val))
-                    }
+        for (idx, cs) in self.cells.iter().enumerate() {
+            if let Some(sc) = cs.fuzzy_constraint() {
+                if let Some(val) = sc.unique_solution() {
+                    out.push((idx / size, idx % size, val))
                 }
             }
         }
         out
     }
 
+    /// Find all values that can only go in one fuzzy cell of a group
+    /// covering a whole row, column, or region.
+    fn find_hidden_uniques(&self) -> SolResult<Vec<(usize, usize, Value)>> {
+        let size = self.size();
+        let all = SoftConstraint::all(size).bits();
+        let mut out = Vec::new();
+        for unit in &self.layout.units {
+            let (mut once, mut several) = (0, 0);
+            for &idx in unit {
+                let bits = match &self.cells[idx] {
+                    CellState::Pinned(v) => SoftConstraint::from(*v).bits(),
+                    CellState::Fuzzy(sc) => sc.bits(),
+                };
+                several |= once & bits;
+                once |= bits;
+            }
+            if once != all {
+                return Err(NoSolError {});
+            }
+            let hidden = once & !several;
+            for &idx in unit {
+                if let CellState::Fuzzy(sc) = &self.cells[idx] {
+                    let mut bits = sc.bits() & hidden;
+                    while bits != 0 {
+                        let val = Value::new(bits.trailing_zeros() as u8 + 1);
+                        out.push((idx / size, idx % size, val));
+                        bits &= bits - 1;
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Pin cells with a single possible value, and values with a single
+    /// possible cell in a row, column, or region, and propagate the
+    /// constraints of the puzzle, until there is nothing left to deduce.
     pub fn maximize_constraints(&mut self) -> SolResult<()> {
         loop {
-            let uniques = self.find_fuzzy_uniques();
+            let mut uniques = self.find_fuzzy_uniques();
             if uniques.is_empty() {
+                uniques = self.find_hidden_uniques()?;
+            }
+            if uniques.is_empty() && !constraint::propagate_all(self)? {
                 break Ok(());
             }
             for (row, col, val) in uniques {
@@ -145,38 +252,97 @@ impl SolutionGrid {
 
     /// Find the fuzzy cell with the least possibilities.
     pub fn find_least_sols_fuzzy(&self) -> Option<(usize, usize, SoftConstraint)> {
-        let mut min_nsols = 10;
+        let size = self.size();
+        let mut min_nsols = u32::MAX;
         let mut out = None;
-        for row in 0..9 {
-            for col in 0..9 {
-                if let Some(sc) = self.0[row][col].fuzzy_constraint() {
-                    let nsols = sc.num_solutions();
-                    if nsols < min_nsols {
-                        out = Some((row, col, sc));
-                        min_nsols = nsols;
-                    }
+        for (idx, cs) in self.cells.iter().enumerate() {
+            if let Some(sc) = cs.fuzzy_constraint() {
+                let nsols = sc.num_solutions();
+                if nsols < min_nsols {
+                    out = Some((idx / size, idx % size, sc));
+                    min_nsols = nsols;
                 }
             }
         }
         out
     }
 
+    /// The value of a cell if it is pinned.
+    pub fn pinned(&self, row: usize, col: usize) -> Option<Value> {
+        self.cells[self.index(row, col)].fixed_val()
+    }
+
+    /// Possible values of a cell as a [`SoftConstraint`], pinned cells having
+    /// exactly one possible value.
+    pub fn candidates(&self, row: usize, col: usize) -> SoftConstraint {
+        match &self.cells[self.index(row, col)] {
+            CellState::Pinned(v) => (*v).into(),
+            CellState::Fuzzy(sc) => *sc,
+        }
+    }
+
     /// All possible values that have not been ruled out yet.
     pub fn possible_values(&self, row: usize, col: usize) -> BTreeSet<Value> {
-        self.0[row][col].all_values()
+        self.cells[self.index(row, col)].all_values()
     }
 }
 
-impl TryFrom<&Puzzle> for SolutionGrid {
-    type Error = NoSolError;
+/// Flag marking a pinned cell in the serialized form of a [`CellState`].
+const PINNED_FLAG: u32 = 1 << 31;
 
-    fn try_from(value: &Puzzle) -> SolResult<Self> {
-        let mut sgrid = Self::default();
-        for row in 0..9 {
-            for col in 0..9 {
-                if let Some(c) = value.get(row, col) {
-                    sgrid.pin(row, col, c)?;
+impl SolutionGrid {
+    /// Number of bytes used by [`Self::encode`] for a grid of a given
+    /// geometry.
+    pub fn encoded_len(geometry: Geometry) -> usize {
+        4 * geometry.n_cells()
+    }
+
+    /// Append a compact representation of the grid to `out`, four
+    /// little-endian bytes per cell.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        for cs in &self.cells {
+            let word = match cs {
+                CellState::Pinned(v) => PINNED_FLAG | v.value() as u32,
+                CellState::Fuzzy(sc) => sc.bits(),
+            };
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// Read a grid written by [`Self::encode`], `None` if the bytes do not
+    /// describe a valid grid.
+    pub fn decode(layout: Arc<Layout>, bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::encoded_len(layout.geometry) {
+            return None;
+        }
+        let size = layout.geometry.size();
+        let all = SoftConstraint::all(size);
+        let cells = bytes
+            .chunks_exact(4)
+            .map(|word| {
+                let word = u32::from_le_bytes(word.try_into().unwrap());
+                if word & PINNED_FLAG != 0 {
+                    let val = (word & !PINNED_FLAG) as usize;
+                    (1..=size)
+                        .contains(&val)
+                        .then(|| CellState::Pinned(Value::new(val as u8)))
+                } else {
+                    let sc = SoftConstraint::from_bits(word)?;
+                    (!sc.difference(all).has_solutions()).then_some(CellState::Fuzzy(sc))
                 }
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { layout, cells })
+    }
+}
+
+impl SolutionGrid {
+    /// Build the grid of a [`Puzzle`] with a layout computed beforehand.
+    pub fn from_puzzle(layout: Arc<Layout>, puzzle: &Puzzle) -> SolResult<Self> {
+        let mut sgrid = Self::new(layout);
+        for ((row, col), c) in puzzle.cells() {
+            if let Some(c) = c {
+                sgrid.pin(row, col, *c)?;
             }
         }
         sgrid.maximize_constraints()?;
@@ -184,14 +350,56 @@ impl TryFrom<&Puzzle> for SolutionGrid {
     }
 }
 
+impl TryFrom<&Puzzle> for SolutionGrid {
+    type Error = NoSolError;
+
+    fn try_from(value: &Puzzle) -> SolResult<Self> {
+        Self::from_puzzle(Arc::new(Layout::new(value)), value)
+    }
+}
+
 impl TryFrom<SolutionGrid> for Grid<u8> {
     type Error = ();
 
     fn try_from(value: SolutionGrid) -> Result<Self, Self::Error> {
         if value.is_solved() {
-            Ok(value.0.map(|r| r.map(|cs| cs.fixed_val().unwrap().value())))
+            let size = value.size();
+            let mut cells = value.cells.into_iter();
+            Ok(Grid::from_fn(size, |_, _| {
+                cells.next().unwrap().fixed_val().unwrap().value()
+            }))
         } else {
             Err(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_single_is_pinned() {
+        // 1 can only go in the first cell of the first row, which sees no
+        // given.
+        let pzl = Puzzle::from_arr([[0, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]]);
+        let Ok(sgrid) = SolutionGrid::try_from(&pzl) else {
+            panic!("puzzle has solutions");
+        };
+        assert_eq!(
+            sgrid.candidates(0, 0).unique_solution(),
+            Some(Value::new(1))
+        );
+        assert_eq!(
+            sgrid.candidates(3, 3).unique_solution(),
+            Some(Value::new(1))
+        );
+    }
+
+    #[test]
+    fn value_without_cell_in_a_unit() {
+        // 1 cannot go anywhere in the first row.
+        let pzl = Puzzle::from_arr([[0, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [1, 0, 0, 0]]);
+        assert!(SolutionGrid::try_from(&pzl).is_err());
+    }
+}
@@ -1,28 +1,158 @@
-use crate::solgrid::SolutionGrid;
-use crate::{Grid, Puzzle};
-use std::collections::BTreeSet;
+use crate::solgrid::{Layout, SolutionGrid};
+use crate::{CandidateGrid, Grid, Puzzle};
+use std::fmt;
 use std::iter::FusedIterator;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// How a search ended.  Searches can be stopped early either through the
+/// "keep going" flag (see [`SolutionIterator::with_handle`]) or by exhausting
+/// their budget (see [`SolutionIterator::with_budget`]), in which case their
+/// result is incomplete and should not be interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchOutcome<T> {
+    /// The whole search space has been explored, the result is complete.
+    Completed(T),
+    /// The search was stopped through its "keep going" flag.
+    Cancelled,
+    /// The search explored as many nodes as its budget allowed.
+    BudgetExceeded,
+}
+
+impl<T> SearchOutcome<T> {
+    /// Whether the search ran to completion.
+    pub fn is_completed(&self) -> bool {
+        matches!(self, SearchOutcome::Completed(_))
+    }
+
+    /// The result of the search, if it ran to completion.
+    pub fn completed(self) -> Option<T> {
+        match self {
+            SearchOutcome::Completed(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Borrow the result of the search.
+    pub fn as_ref(&self) -> SearchOutcome<&T> {
+        match self {
+            SearchOutcome::Completed(v) => SearchOutcome::Completed(v),
+            SearchOutcome::Cancelled => SearchOutcome::Cancelled,
+            SearchOutcome::BudgetExceeded => SearchOutcome::BudgetExceeded,
+        }
+    }
+
+    /// Map the result of a completed search, leaving interruptions untouched.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> SearchOutcome<U> {
+        match self {
+            SearchOutcome::Completed(v) => SearchOutcome::Completed(f(v)),
+            SearchOutcome::Cancelled => SearchOutcome::Cancelled,
+            SearchOutcome::BudgetExceeded => SearchOutcome::BudgetExceeded,
+        }
+    }
+}
+
+/// Magic bytes starting every [`SolutionIterator::snapshot`].
+const SNAPSHOT_MAGIC: &[u8; 4] = b"SDKS";
+/// Version of the snapshot format.
+const SNAPSHOT_VERSION: u8 = 3;
+/// Length of the snapshot header: magic, version, box dimensions, puzzle
+/// fingerprint, explored count and number of grids on the stack.
+const SNAPSHOT_HEADER_LEN: usize = 4 + 1 + 2 + 8 + 8 + 4;
+
+/// A hash of the puzzle file format of `problem` (64-bit FNV-1a), which
+/// unlike [`std::hash::DefaultHasher`] is the same across Rust releases.
+fn fingerprint(problem: &Puzzle) -> u64 {
+    problem
+        .to_string()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Error signaling that bytes given to [`SolutionIterator::resume`] are not a
+/// valid snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The bytes do not start with the expected magic bytes.
+    NotASnapshot,
+    /// The snapshot was written by an incompatible version of the library.
+    UnsupportedVersion(u8),
+    /// The snapshot was taken on a puzzle of a different geometry.
+    GeometryMismatch,
+    /// The snapshot was taken on a different puzzle.
+    PuzzleMismatch,
+    /// The snapshot is shorter or longer than announced in its header.
+    BadLength,
+    /// A grid stored in the snapshot is not valid.
+    CorruptedGrid,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a solver snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {v}")
+            }
+            SnapshotError::GeometryMismatch => {
+                write!(f, "snapshot was taken on a puzzle of a different geometry")
+            }
+            SnapshotError::PuzzleMismatch => {
+                write!(f, "snapshot was taken on a different puzzle")
+            }
+            SnapshotError::BadLength => write!(f, "snapshot has an unexpected length"),
+            SnapshotError::CorruptedGrid => write!(f, "snapshot contains an invalid grid"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
 /// Iterate through the solution of a given [`Puzzle`].  Instances are usually
 /// obtained via [`Puzzle::solutions`].
 #[derive(Clone)]
 pub struct SolutionIterator {
+    layout: Arc<Layout>,
+    /// See [`fingerprint`], checked when resuming from a snapshot.
+    fingerprint: u64,
     stack: Vec<SolutionGrid>,
     keep_going: Arc<AtomicBool>,
+    budget: Option<u64>,
+    explored: u64,
+    outcome: Option<SearchOutcome<()>>,
 }
 
 impl SolutionIterator {
     /// Create an iterator over the solutions of the given [`Puzzle`].
     pub fn new(problem: &Puzzle) -> Self {
-        let mut stack = Vec::with_capacity(81); // could do better
-        if let Ok(grid) = problem.try_into() {
-            stack.push(grid);
-        }
+        let layout = Arc::new(Layout::new(problem));
+        let root = SolutionGrid::from_puzzle(layout.clone(), problem).ok();
+        Self::from_root(problem, layout, root)
+    }
+
+    /// Create an iterator over the solutions of a [`Puzzle`] that only use
+    /// the candidates of a [`CandidateGrid`], see [`CandidateGrid::solutions`].
+    pub fn from_candidates(candidates: &CandidateGrid) -> Self {
+        let layout = Arc::new(Layout::new(candidates.puzzle()));
+        let root = candidates.root(layout.clone());
+        Self::from_root(candidates.puzzle(), layout, root)
+    }
+
+    /// Create an iterator over the solutions of a grid of `problem`, `None`
+    /// if it is known to have none.
+    fn from_root(problem: &Puzzle, layout: Arc<Layout>, root: Option<SolutionGrid>) -> Self {
+        let mut stack = Vec::with_capacity(layout.geometry().n_cells()); // could do better
+        stack.extend(root);
         Self {
+            layout,
+            fingerprint: fingerprint(problem),
             stack,
             keep_going: Arc::new(true.into()),
+            budget: None,
+            explored: 0,
+            outcome: None,
         }
     }
 
@@ -57,6 +187,136 @@ impl SolutionIterator {
         Self { keep_going, ..self }
     }
 
+    /// Limit the number of nodes of the search tree the iterator is allowed to
+    /// explore from now on.  Once that budget is exhausted, the iterator stops
+    /// yielding solutions and [`Self::outcome`] reports
+    /// [`SearchOutcome::BudgetExceeded`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, SearchOutcome};
+    ///
+    /// let mut sols = Puzzle::default().solutions().with_budget(10);
+    /// assert!(sols.next().is_none());
+    /// assert_eq!(sols.outcome(), Some(SearchOutcome::BudgetExceeded));
+    /// ```
+    pub fn with_budget(self, max_nodes: u64) -> Self {
+        Self {
+            budget: Some(self.explored.saturating_add(max_nodes)),
+            ..self
+        }
+    }
+
+    /// Number of nodes of the search tree explored so far.
+    pub fn explored(&self) -> u64 {
+        self.explored
+    }
+
+    /// Why the iterator stopped yielding solutions.  This is `None` as long as
+    /// the iterator has not returned `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, SearchOutcome, Value};
+    ///
+    /// let mut pzl = Puzzle::default();
+    /// pzl.pin(0, 0, Value::new(1));
+    /// pzl.pin(0, 1, Value::new(1));
+    /// let mut sols = pzl.solutions();
+    /// assert!(sols.next().is_none());
+    /// // The puzzle genuinely has no solution.
+    /// assert_eq!(sols.outcome(), Some(SearchOutcome::Completed(())));
+    /// ```
+    pub fn outcome(&self) -> Option<SearchOutcome<()>> {
+        self.outcome
+    }
+
+    /// Serialize the state of the search, i.e. the nodes of the search tree
+    /// that remain to be explored.  [`Self::resume`] restores an iterator
+    /// that yields exactly the solutions this one has not yielded yet.  The
+    /// puzzle, the "keep going" flag and the budget are not part of the
+    /// snapshot.
+    ///
+    /// An iterator stopped through its handle or its budget can still be
+    /// snapshotted, no node is lost when the search is interrupted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Puzzle, SolutionIterator};
+    ///
+    /// let pzl = Puzzle::default();
+    /// let mut sols = pzl.solutions();
+    /// let first = sols.next().unwrap();
+    /// let bytes = sols.snapshot();
+    ///
+    /// let mut resumed = SolutionIterator::resume(&pzl, &bytes).unwrap();
+    /// let second = resumed.next().unwrap();
+    /// assert_ne!(first, second);
+    /// assert_eq!(Some(second), sols.next());
+    /// ```
+    pub fn snapshot(&self) -> Vec<u8> {
+        let geometry = self.layout.geometry();
+        let grid_len = SolutionGrid::encoded_len(geometry);
+        let mut out = Vec::with_capacity(SNAPSHOT_HEADER_LEN + self.stack.len() * grid_len);
+        out.extend_from_slice(SNAPSHOT_MAGIC);
+        out.push(SNAPSHOT_VERSION);
+        out.push(geometry.box_rows() as u8);
+        out.push(geometry.box_cols() as u8);
+        out.extend_from_slice(&self.fingerprint.to_le_bytes());
+        out.extend_from_slice(&self.explored.to_le_bytes());
+        out.extend_from_slice(&(self.stack.len() as u32).to_le_bytes());
+        for grid in &self.stack {
+            grid.encode(&mut out);
+        }
+        out
+    }
+
+    /// Restore an iterator from bytes obtained with [`Self::snapshot`] on an
+    /// iterator through the solutions of `problem`.  Snapshots taken on
+    /// another puzzle are rejected, the comparison being based on the puzzle
+    /// file format of `problem`.
+    pub fn resume(problem: &Puzzle, bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < SNAPSHOT_HEADER_LEN || &bytes[..4] != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        if bytes[4] != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(bytes[4]));
+        }
+        let geometry = problem.geometry();
+        if (bytes[5] as usize, bytes[6] as usize) != (geometry.box_rows(), geometry.box_cols()) {
+            return Err(SnapshotError::GeometryMismatch);
+        }
+        let fingerprint = u64::from_le_bytes(bytes[7..15].try_into().unwrap());
+        if fingerprint != self::fingerprint(problem) {
+            return Err(SnapshotError::PuzzleMismatch);
+        }
+        let explored = u64::from_le_bytes(bytes[15..23].try_into().unwrap());
+        let n_grids = u32::from_le_bytes(bytes[23..27].try_into().unwrap()) as usize;
+        let body = &bytes[SNAPSHOT_HEADER_LEN..];
+        let grid_len = SolutionGrid::encoded_len(geometry);
+        if body.len() != n_grids * grid_len {
+            return Err(SnapshotError::BadLength);
+        }
+        let layout = Arc::new(Layout::new(problem));
+        let stack = body
+            .chunks_exact(grid_len)
+            .map(|chunk| SolutionGrid::decode(layout.clone(), chunk))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SnapshotError::CorruptedGrid)?;
+        Ok(Self {
+            layout,
+            fingerprint,
+            stack,
+            keep_going: Arc::new(true.into()),
+            budget: None,
+            explored,
+            outcome: None,
+        })
+    }
+
     fn maximize_and_push(&mut self, mut grid: SolutionGrid) {
         if grid.maximize_constraints().is_ok() {
             self.stack.push(grid);
@@ -68,11 +328,22 @@ impl Iterator for SolutionIterator {
     type Item = Grid<u8>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.outcome.is_some() {
+            return None;
+        }
         while let Some(mut grid) = self.stack.pop() {
             if !self.keep_going.load(Ordering::Relaxed) {
-                self.stack.truncate(0);
-                continue;
+                // The stack is kept intact, the search is merely suspended.
+                self.stack.push(grid);
+                self.outcome = Some(SearchOutcome::Cancelled);
+                return None;
+            }
+            if self.budget.is_some_and(|b| self.explored >= b) {
+                self.stack.push(grid);
+                self.outcome = Some(SearchOutcome::BudgetExceeded);
+                return None;
             }
+            self.explored += 1;
             if grid.is_solved() {
                 return Some(Self::Item::try_from(grid).unwrap());
             } else if let Some((row, col, sc)) = grid.find_least_sols_fuzzy() {
@@ -87,68 +358,75 @@ impl Iterator for SolutionIterator {
                 }
             }
         }
+        self.outcome = Some(SearchOutcome::Completed(()));
         None
     }
 }
 
 impl FusedIterator for SolutionIterator {}
 
-/// A helper to find all the values in each cell that lead to a
-/// solvable [`Puzzle`].
-pub struct PossibleValuesFinder {
-    keep_going: Arc<AtomicBool>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::triple_sol_puzzle;
+    use crate::Geometry;
 
-impl PossibleValuesFinder {
-    pub fn new() -> Self {
-        Self {
-            keep_going: Arc::new(true.into()),
-        }
+    #[test]
+    fn resume_yields_remaining_solutions() {
+        let all: Vec<_> = triple_sol_puzzle().solutions().collect();
+        let mut sols = triple_sol_puzzle().solutions();
+        assert_eq!(sols.next().as_ref(), all.first());
+        let resumed: Vec<_> = SolutionIterator::resume(&triple_sol_puzzle(), &sols.snapshot())
+            .unwrap()
+            .collect();
+        assert_eq!(resumed, all[1..]);
     }
 
-    /// Set the `Arc<AtomicBool>` to use as a "keep going" flag. Similarly to
-    /// [`SolutionIterator::with_handle`], this is useful to have a way to stop
-    /// a separate thread running [`Self::search`].
-    pub fn with_handle(keep_going: Arc<AtomicBool>) -> Self {
-        Self { keep_going }
-    }
-
-    /// Search all the values in the non-pinned cells of a given [`Puzzle`]
-    /// that lead to a solvable [`Puzzle`].
-    pub fn search(self, puzzle: &Puzzle) -> Grid<BTreeSet<u8>> {
-        let mut pvs = Grid::default();
-        let solgrid: SolutionGrid = match puzzle.try_into() {
-            Ok(sg) => sg,
-            Err(_) => return pvs,
-        };
-        for row in 0..9 {
-            for col in 0..9 {
-                for val in solgrid.possible_values(row, col) {
-                    if !self.keep_going.load(Ordering::Relaxed) {
-                        return Grid::default();
-                    }
-                    if pvs[row][col].contains(&val.value()) {
-                        continue;
-                    }
-                    let mut pzl: Puzzle = puzzle.clone();
-                    pzl.pin(row, col, val);
-                    let mut sols = pzl.solutions().with_handle(self.keep_going.clone());
-                    if let Some(sol) = sols.next() {
-                        sol.iter().enumerate().for_each(|(ir, r)| {
-                            r.iter().enumerate().for_each(|(ic, &v)| {
-                                pvs[ir][ic].insert(v);
-                            });
-                        });
-                    }
-                }
-            }
-        }
-        pvs
+    #[test]
+    fn resume_after_budget_exceeded() {
+        let all: Vec<_> = triple_sol_puzzle().solutions().collect();
+        let mut sols = triple_sol_puzzle().solutions().with_budget(1);
+        let before: Vec<_> = sols.by_ref().collect();
+        assert_eq!(sols.outcome(), Some(SearchOutcome::BudgetExceeded));
+        let mut resumed = SolutionIterator::resume(&triple_sol_puzzle(), &sols.snapshot()).unwrap();
+        let after: Vec<_> = resumed.by_ref().collect();
+        assert_eq!(resumed.outcome(), Some(SearchOutcome::Completed(())));
+        assert_eq!([before, after].concat(), all);
+    }
+
+    #[test]
+    fn resume_rejects_other_puzzle() {
+        let mut sols = triple_sol_puzzle().solutions();
+        sols.next();
+        let mut other = triple_sol_puzzle();
+        other.unpin(0, 0);
+        assert_eq!(
+            SolutionIterator::resume(&other, &sols.snapshot()).err(),
+            Some(SnapshotError::PuzzleMismatch)
+        );
     }
-}
 
-impl Default for PossibleValuesFinder {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn resume_rejects_garbage() {
+        let pzl = Puzzle::default();
+        let bytes = pzl.solutions().snapshot();
+        assert_eq!(
+            SolutionIterator::resume(&pzl, b"nope").err(),
+            Some(SnapshotError::NotASnapshot)
+        );
+        assert_eq!(
+            SolutionIterator::resume(&pzl, &bytes[..bytes.len() - 1]).err(),
+            Some(SnapshotError::BadLength)
+        );
+        assert_eq!(
+            SolutionIterator::resume(&Puzzle::new(Geometry::new(2, 3)), &bytes).err(),
+            Some(SnapshotError::GeometryMismatch)
+        );
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() = 0x7f;
+        assert_eq!(
+            SolutionIterator::resume(&pzl, &corrupted).err(),
+            Some(SnapshotError::CorruptedGrid)
+        );
     }
 }
@@ -0,0 +1,162 @@
+use crate::{Grid, Puzzle, Value};
+
+/// All the subsets of `k` values among `1..=size`, as bitmasks.
+fn value_subsets(size: usize, k: usize) -> Vec<u32> {
+    (0u32..1 << size)
+        .filter(|mask| mask.count_ones() as usize == k)
+        .collect()
+}
+
+impl Puzzle {
+    /// Minimal unavoidable sets of a solution of the rules of the puzzle,
+    /// whose givens are ignored: sets of cells whose values can be permuted
+    /// into another solution, so that every puzzle with this solution has a
+    /// given in each of them.  Sets are listed by size, then by cells in
+    /// reading order, each in reading order.
+    ///
+    /// Sets are found among the cells holding up to `max_values` distinct
+    /// values, all of them for that many values: with 2, this includes the
+    /// unique rectangles, 4 cells on two rows, two columns, and two boxes.
+    /// The work grows quickly with `max_values`, 2 or 3 are the usual
+    /// choices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `solution` is not a solution of the rules of the puzzle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_solver::{Geometry, Grid, Puzzle};
+    ///
+    /// let rules = Puzzle::new(Geometry::new(2, 2));
+    /// let sol: Grid<u8> = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]].into();
+    /// let sets = rules.unavoidable_sets(&sol, 2);
+    /// // Swapping the 1 and 3 of the bottom rows in the second and fourth
+    /// // columns gives another solution.
+    /// assert!(sets.contains(&vec![(2, 1), (2, 3), (3, 1), (3, 3)]));
+    /// // A puzzle with this solution has a given in each set.
+    /// let pzl = Puzzle::from_grid(Geometry::new(2, 2), sol).minimized();
+    /// assert!(sets.iter().all(|set| set.iter().any(|&(r, c)| pzl.get(r, c).is_some())));
+    /// ```
+    pub fn unavoidable_sets(
+        &self,
+        solution: &Grid<u8>,
+        max_values: usize,
+    ) -> Vec<Vec<(usize, usize)>> {
+        let size = self.geometry().size();
+        let mut rules = self.clone();
+        for row in 0..size {
+            for col in 0..size {
+                rules.unpin(row, col);
+            }
+        }
+        assert!(
+            rules.check(solution).is_empty(),
+            "Not a solution of the rules of the puzzle."
+        );
+        let mut sets: Vec<Vec<(usize, usize)>> = Vec::new();
+        for k in 2..=max_values.min(size) {
+            for values in value_subsets(size, k) {
+                let mut pzl = rules.clone();
+                for ((row, col), &val) in solution.cells() {
+                    if values & (1 << (val - 1)) == 0 {
+                        pzl.pin(row, col, Value::new(val));
+                    }
+                }
+                for other in pzl.solutions() {
+                    let set = solution
+                        .cells()
+                        .filter(|&((row, col), &val)| other[row][col] != val)
+                        .map(|(pos, _)| pos)
+                        .collect::<Vec<_>>();
+                    if !set.is_empty() {
+                        sets.push(set);
+                    }
+                }
+            }
+        }
+        sets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        sets.dedup();
+        let mut minimal: Vec<Vec<(usize, usize)>> = Vec::new();
+        for set in sets {
+            if !minimal
+                .iter()
+                .any(|smaller| smaller.iter().all(|cell| set.contains(cell)))
+            {
+                minimal.push(set);
+            }
+        }
+        minimal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CLASSIC_SOLUTION;
+    use crate::Geometry;
+
+    fn solution() -> Grid<u8> {
+        Puzzle::from_line(CLASSIC_SOLUTION)
+            .unwrap()
+            .solutions()
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn sets_are_unavoidable_and_minimal() {
+        let sol = solution();
+        let sets = Puzzle::default().unavoidable_sets(&sol, 2);
+        assert!(!sets.is_empty());
+        assert!(sets.windows(2).all(|w| w[0].len() <= w[1].len()));
+        for set in &sets {
+            // Without givens in the set, the puzzle has another solution.
+            let mut pzl = Puzzle::from_grid(Geometry::CLASSIC, sol.clone());
+            for &(row, col) in set {
+                pzl.unpin(row, col);
+            }
+            assert!(pzl.solutions().take(2).count() > 1);
+            // With any cell of the set as a given, the others do not lead to
+            // another solution.
+            for &(row, col) in set {
+                pzl.pin(row, col, Value::new(sol[row][col]));
+                assert_eq!(pzl.solutions().take(2).count(), 1);
+                pzl.unpin(row, col);
+            }
+        }
+    }
+
+    #[test]
+    fn unique_rectangles_are_found() {
+        let sol = solution();
+        let sets = Puzzle::default().unavoidable_sets(&sol, 2);
+        let geometry = Geometry::CLASSIC;
+        for (r1, r2) in (0..9).flat_map(|r1| (r1 + 1..9).map(move |r2| (r1, r2))) {
+            for (c1, c2) in (0..9).flat_map(|c1| (c1 + 1..9).map(move |c2| (c1, c2))) {
+                let boxes =
+                    [(r1, c1), (r1, c2), (r2, c1), (r2, c2)].map(|(r, c)| geometry.box_index(r, c));
+                let rectangle = sol[r1][c1] == sol[r2][c2]
+                    && sol[r1][c2] == sol[r2][c1]
+                    && boxes
+                        .iter()
+                        .collect::<std::collections::BTreeSet<_>>()
+                        .len()
+                        <= 2;
+                let set = vec![(r1, c1), (r1, c2), (r2, c1), (r2, c2)];
+                assert_eq!(sets.contains(&set), rectangle);
+            }
+        }
+    }
+
+    #[test]
+    fn more_values_give_more_sets() {
+        let rules = Puzzle::new(Geometry::new(2, 3));
+        let sol = rules.solutions().next().unwrap();
+        let pairs = rules.unavoidable_sets(&sol, 2);
+        let triples = rules.unavoidable_sets(&sol, 3);
+        assert!(pairs.iter().all(|set| triples.contains(set)));
+        assert!(triples.len() > pairs.len());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Arrows: the value in the circle of each arrow is the sum of the values
+/// along the arrow.  Values may repeat along an arrow if the other rules
+/// allow it.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Arrows, Puzzle};
+///
+/// // The circle in the center, the arrow pointing up.
+/// let arrows = Arrows::new(9, vec![vec![(4, 4), (3, 4), (2, 4)]]).unwrap();
+/// let pzl = Puzzle::default().with_constraint(arrows);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_eq!(sol[4][4], sol[3][4] + sol[2][4]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Arrows {
+    size: usize,
+    arrows: Vec<Vec<(usize, usize)>>,
+}
+
+impl Arrows {
+    /// Build arrows in a grid of a given size, each given by the positions
+    /// of its circle followed by the cells along it, 0-indexed.
+    pub fn new(size: usize, arrows: Vec<Vec<(usize, usize)>>) -> Result<Self, ShapeError> {
+        for (i, arrow) in arrows.iter().enumerate() {
+            if arrow.len() < 2 {
+                return Err(ShapeError::BadLength(i));
+            }
+            if arrow.len() - 1 > size {
+                return Err(ShapeError::ImpossibleClue(i));
+            }
+            shape::check_cells(size, arrow)?;
+        }
+        Ok(Self { size, arrows })
+    }
+
+    /// The arrows, each starting with its circle.
+    pub fn arrows(&self) -> &[Vec<(usize, usize)>] {
+        &self.arrows
+    }
+}
+
+impl Constraint for Arrows {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for arrow in &self.arrows {
+            let (&(crow, ccol), cells) = arrow.split_first().unwrap();
+            let bounds: Vec<(u32, u32)> = cells
+                .iter()
+                .map(|&(row, col)| {
+                    let cands = grid.candidates(row, col);
+                    (shape::min_value(cands), shape::max_value(cands))
+                })
+                .collect();
+            let min_sum: u32 = bounds.iter().map(|b| b.0).sum();
+            let max_sum: u32 = bounds.iter().map(|b| b.1).sum();
+            grid.restrict(crow, ccol, shape::value_range(min_sum, max_sum))?;
+            let circle = grid.candidates(crow, ccol);
+            let (cmin, cmax) = (shape::min_value(circle), shape::max_value(circle));
+            for (&(row, col), &(min, max)) in cells.iter().zip(&bounds) {
+                let low = cmin.saturating_sub(max_sum - max);
+                let high = cmax.saturating_sub(min_sum - min);
+                grid.restrict(row, col, shape::value_range(low, high))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Puzzle};
+
+    #[test]
+    fn circles_are_sums_of_arrows() {
+        let arrow = vec![(1, 1), (2, 2), (3, 0)];
+        let arrows = Arrows::new(4, vec![arrow.clone()]).unwrap();
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| sol[1][1] == sol[2][2] + sol[3][0])
+            .collect();
+        assert!(!expected.is_empty());
+        let sols: Vec<_> = classic.with_constraint(arrows).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn arrows_prune_candidates() {
+        let arrows = Arrows::new(9, vec![vec![(0, 0), (1, 1), (2, 2), (3, 3)]]).unwrap();
+        let pzl = Puzzle::default().with_constraint(arrows);
+        let grid = propagated(&pzl);
+        // The circle is at least 1 + 1 + 1, arrow cells are at most 9 - 2.
+        assert_eq!(grid.candidates(0, 0).bits(), 0b111_111_100);
+        assert_eq!(grid.candidates(2, 2).bits(), 0b001_111_111);
+    }
+
+    #[test]
+    fn invalid_arrows() {
+        assert_eq!(
+            Arrows::new(4, vec![vec![(0, 0)]]),
+            Err(ShapeError::BadLength(0))
+        );
+        let long = (0..4).map(|col| (0, col)).chain([(1, 0), (1, 1)]).collect();
+        assert_eq!(
+            Arrows::new(4, vec![long]),
+            Err(ShapeError::ImpossibleClue(0))
+        );
+    }
+}
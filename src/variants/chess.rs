@@ -0,0 +1,153 @@
+use crate::constraint::Constraint;
+
+/// Pairs of distinct cells of a grid of a given size that are a given set of
+/// moves apart, each pair listed once.
+fn move_pairs(size: usize, moves: &[(isize, isize)]) -> Vec<Vec<(usize, usize)>> {
+    let mut pairs = Vec::new();
+    for row in 0..size {
+        for col in 0..size {
+            for &(drow, dcol) in moves {
+                let (orow, ocol) = (row as isize + drow, col as isize + dcol);
+                if (0..size as isize).contains(&orow) && (0..size as isize).contains(&ocol) {
+                    let other = (orow as usize, ocol as usize);
+                    if (row, col) < other {
+                        pairs.push(vec![(row, col), other]);
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Cells a knight's move apart must hold distinct values.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{AntiKnight, Puzzle};
+///
+/// let pzl = Puzzle::default().with_constraint(AntiKnight);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_ne!(sol[0][0], sol[1][2]);
+/// assert_ne!(sol[0][0], sol[2][1]);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct AntiKnight;
+
+impl Constraint for AntiKnight {
+    fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        move_pairs(size, &[(1, 2), (2, 1), (1, -2), (2, -1)])
+    }
+}
+
+/// Cells a king's move apart must hold distinct values.  Since cells sharing
+/// an edge also share a row or column, this only adds diagonally adjacent
+/// cells to the classic rules.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{AntiKing, Puzzle};
+///
+/// let pzl = Puzzle::default().with_constraint(AntiKing);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_ne!(sol[2][2], sol[3][3]);
+/// assert_ne!(sol[2][3], sol[3][2]);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct AntiKing;
+
+impl Constraint for AntiKing {
+    fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        move_pairs(size, &[(0, 1), (1, -1), (1, 0), (1, 1)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Puzzle;
+
+    /// Givens of `puzzle` under the classic rules only.
+    fn classic(puzzle: &Puzzle) -> Puzzle {
+        let mut out = Puzzle::default();
+        for row in 0..9 {
+            for col in 0..9 {
+                if let Some(val) = puzzle.get(row, col) {
+                    out.pin(row, col, val);
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn move_pairs_are_listed_once() {
+        assert_eq!(AntiKnight.groups(3).len(), 8);
+        assert_eq!(AntiKing.groups(2).len(), 6);
+        assert_eq!(AntiKnight.groups(9).len(), 2 * 2 * 7 * 8);
+    }
+
+    #[test]
+    fn anti_knight_single_sol() {
+        let pzl = Puzzle::from_arr([
+            [0, 3, 0, 0, 1, 0, 0, 0, 0],
+            [0, 0, 5, 0, 0, 0, 4, 0, 0],
+            [7, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 5, 0, 0, 0],
+            [0, 0, 0, 3, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0, 8],
+            [0, 0, 0, 0, 0, 0, 1, 0, 0],
+            [0, 6, 0, 0, 4, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0, 0, 9, 0, 0],
+        ])
+        .with_constraint(AntiKnight);
+        let expected = [
+            [4, 3, 2, 5, 1, 6, 7, 8, 9],
+            [8, 9, 5, 7, 3, 2, 4, 1, 6],
+            [7, 1, 6, 4, 8, 9, 2, 3, 5],
+            [1, 7, 3, 8, 9, 5, 6, 2, 4],
+            [6, 2, 8, 3, 7, 4, 5, 9, 1],
+            [9, 5, 4, 6, 2, 1, 3, 7, 8],
+            [3, 4, 7, 9, 5, 8, 1, 6, 2],
+            [2, 6, 9, 1, 4, 7, 8, 5, 3],
+            [5, 8, 1, 2, 6, 3, 9, 4, 7],
+        ];
+        let mut sols = pzl.solutions();
+        assert_eq!(sols.next().unwrap(), expected);
+        assert!(sols.next().is_none());
+        assert!(classic(&pzl).solutions().nth(1).is_some());
+    }
+
+    #[test]
+    fn anti_king_single_sol() {
+        let pzl = Puzzle::from_arr([
+            [0, 2, 0, 0, 1, 0, 0, 0, 9],
+            [0, 0, 0, 0, 0, 0, 2, 4, 0],
+            [0, 5, 0, 0, 0, 0, 0, 0, 6],
+            [0, 0, 0, 5, 0, 0, 0, 7, 0],
+            [0, 7, 0, 0, 0, 0, 0, 0, 0],
+            [3, 0, 0, 8, 6, 0, 0, 1, 0],
+            [0, 0, 7, 0, 0, 0, 0, 0, 0],
+            [5, 0, 0, 0, 0, 0, 0, 6, 7],
+            [0, 0, 8, 0, 0, 0, 0, 0, 0],
+        ])
+        .with_constraint(AntiKing);
+        let expected = [
+            [6, 2, 3, 4, 1, 5, 7, 8, 9],
+            [7, 8, 1, 6, 9, 3, 2, 4, 5],
+            [4, 5, 9, 2, 7, 8, 1, 3, 6],
+            [2, 1, 6, 5, 3, 4, 9, 7, 8],
+            [8, 7, 4, 9, 2, 1, 6, 5, 3],
+            [3, 9, 5, 8, 6, 7, 4, 1, 2],
+            [1, 6, 7, 3, 5, 2, 8, 9, 4],
+            [5, 4, 2, 1, 8, 9, 3, 6, 7],
+            [9, 3, 8, 7, 4, 6, 5, 2, 1],
+        ];
+        let mut sols = pzl.solutions();
+        assert_eq!(sols.next().unwrap(), expected);
+        assert!(sols.next().is_none());
+        assert!(classic(&pzl).solutions().nth(1).is_some());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::constraint::Constraint;
+
+/// Both main diagonals of the grid must hold distinct values, as in Sudoku X.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Diagonals, Puzzle};
+///
+/// let pzl = Puzzle::default().with_constraint(Diagonals);
+/// let sol = pzl.solutions().next().unwrap();
+/// let mut diag: Vec<_> = (0..9).map(|i| sol[i][8 - i]).collect();
+/// diag.sort();
+/// assert_eq!(diag, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Diagonals;
+
+impl Diagonals {
+    /// Whether a cell lies on one of the diagonals of a grid of a given size.
+    pub fn contains(size: usize, row: usize, col: usize) -> bool {
+        row == col || row + col + 1 == size
+    }
+}
+
+impl Constraint for Diagonals {
+    fn groups(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        vec![
+            (0..size).map(|i| (i, i)).collect(),
+            (0..size).map(|i| (i, size - 1 - i)).collect(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Puzzle;
+
+    fn sudoku_x() -> Puzzle {
+        Puzzle::from_arr([
+            [0, 4, 0, 0, 6, 0, 0, 0, 0],
+            [0, 0, 2, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 5, 0],
+            [0, 0, 4, 0, 0, 0, 9, 0, 0],
+            [2, 0, 0, 9, 1, 0, 0, 4, 0],
+            [0, 8, 0, 0, 0, 2, 0, 0, 5],
+            [0, 0, 0, 7, 0, 0, 8, 0, 0],
+            [0, 0, 0, 0, 8, 0, 0, 0, 3],
+            [0, 0, 8, 0, 0, 0, 7, 0, 0],
+        ])
+        .with_constraint(Diagonals)
+    }
+
+    #[test]
+    fn sudoku_x_single_sol() {
+        let pzl = sudoku_x();
+        let mut sols = pzl.solutions();
+        let expected = [
+            [5, 4, 1, 3, 6, 9, 2, 8, 7],
+            [6, 7, 2, 8, 5, 1, 4, 3, 9],
+            [8, 9, 3, 2, 4, 7, 6, 5, 1],
+            [1, 5, 4, 6, 3, 8, 9, 7, 2],
+            [2, 6, 7, 9, 1, 5, 3, 4, 8],
+            [3, 8, 9, 4, 7, 2, 1, 6, 5],
+            [4, 1, 5, 7, 9, 3, 8, 2, 6],
+            [7, 2, 6, 1, 8, 4, 5, 9, 3],
+            [9, 3, 8, 5, 2, 6, 7, 1, 4],
+        ];
+        assert_eq!(sols.next().unwrap(), expected);
+        assert!(sols.next().is_none());
+        let pvals = pzl.possible_values();
+        assert!(pvals.iter().flatten().all(|vals| vals.len() == 1));
+    }
+
+    #[test]
+    fn diagonals_are_selectable() {
+        let classic = sudoku_x().without_constraint::<Diagonals>();
+        assert!(classic.constraint::<Diagonals>().is_none());
+        assert!(classic.solutions().nth(1).is_some());
+        assert!(Diagonals::contains(9, 2, 6));
+        assert!(!Diagonals::contains(9, 2, 5));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Relation marked on the edge between two orthogonally adjacent cells.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Marker {
+    /// White kropki dot: the values are consecutive.
+    White,
+    /// Black kropki dot: one value is twice the other.
+    Black,
+    /// The values add up to 10.
+    X,
+    /// The values add up to 5.
+    V,
+}
+
+impl Marker {
+    /// Whether two values are related by the marker.
+    pub fn holds(self, a: u8, b: u8) -> bool {
+        match self {
+            Marker::White => a.abs_diff(b) == 1,
+            Marker::Black => a == 2 * b || b == 2 * a,
+            Marker::X => a + b == 10,
+            Marker::V => a + b == 5,
+        }
+    }
+}
+
+/// A [`Marker`] between two cells.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Dot {
+    marker: Marker,
+    cells: [(usize, usize); 2],
+}
+
+impl Dot {
+    /// Marker between cells at given `(row, col)` positions, 0-indexed.
+    pub fn new(marker: Marker, a: (usize, usize), b: (usize, usize)) -> Self {
+        Self {
+            marker,
+            cells: [a, b],
+        }
+    }
+
+    /// Relation between the values of the cells.
+    pub fn marker(&self) -> Marker {
+        self.marker
+    }
+
+    /// Positions of the two cells.
+    pub fn cells(&self) -> [(usize, usize); 2] {
+        self.cells
+    }
+}
+
+/// Kropki dots and XV markers between adjacent cells.  Under a negative
+/// constraint for a marker, all the pairs of adjacent cells where the relation
+/// holds are marked, so pairs without any marker must not satisfy it.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Dot, Dots, Marker, Puzzle};
+///
+/// // A V between the first two cells, and no other consecutive pairs.
+/// let dots = Dots::new(9, vec![Dot::new(Marker::V, (0, 0), (0, 1))])
+///     .unwrap()
+///     .with_negative(Marker::White);
+/// let pzl = Puzzle::default().with_constraint(dots);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_eq!(sol[0][0] + sol[0][1], 5);
+/// assert!(sol[0][1..].windows(2).all(|w| w[0].abs_diff(w[1]) != 1));
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Dots {
+    size: usize,
+    dots: Vec<Dot>,
+    negative: Vec<Marker>,
+    /// Bitmasks of the values related to each value, for each dot.
+    partners: Vec<Vec<u32>>,
+    /// Pairs of adjacent cells without a dot.
+    unmarked: Vec<[(usize, usize); 2]>,
+    /// Bitmasks of the values unrelated to each value by negative markers.
+    unrelated: Vec<u32>,
+}
+
+impl Dots {
+    /// Build markers for a grid of a given size, without negative constraint.
+    pub fn new(size: usize, dots: Vec<Dot>) -> Result<Self, ShapeError> {
+        let mut partners_of = Vec::with_capacity(dots.len());
+        for (i, dot) in dots.iter().enumerate() {
+            shape::check_cells(size, &dot.cells)?;
+            let [(r1, c1), (r2, c2)] = dot.cells;
+            if r1.abs_diff(r2) + c1.abs_diff(c2) != 1 {
+                return Err(ShapeError::NotAdjacent(i));
+            }
+            let partners = shape::partners(size, |a, b| dot.marker.holds(a, b));
+            if partners.iter().all(|&p| p == 0) {
+                return Err(ShapeError::ImpossibleClue(i));
+            }
+            partners_of.push(partners);
+        }
+        let is_marked = |a, b| {
+            dots.iter()
+                .any(|dot| dot.cells == [a, b] || dot.cells == [b, a])
+        };
+        let unmarked = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .flat_map(|(row, col)| [((row, col), (row, col + 1)), ((row, col), (row + 1, col))])
+            .filter(|&(_, (row, col))| row < size && col < size)
+            .filter(|&(a, b)| !is_marked(a, b))
+            .map(|(a, b)| [a, b])
+            .collect();
+        Ok(Self {
+            size,
+            dots,
+            negative: Vec::new(),
+            partners: partners_of,
+            unmarked,
+            unrelated: shape::partners(size, |_, _| true),
+        })
+    }
+
+    /// Add the negative constraint for a marker: pairs of adjacent cells
+    /// without any marker must not satisfy its relation.
+    pub fn with_negative(mut self, marker: Marker) -> Self {
+        if !self.negative.contains(&marker) {
+            self.negative.push(marker);
+        }
+        let negative = &self.negative;
+        self.unrelated = shape::partners(self.size, |a, b| !negative.iter().any(|m| m.holds(a, b)));
+        self
+    }
+
+    /// The markers.
+    pub fn dots(&self) -> &[Dot] {
+        &self.dots
+    }
+
+    /// Markers under a negative constraint.
+    pub fn negative(&self) -> &[Marker] {
+        &self.negative
+    }
+}
+
+impl Constraint for Dots {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for (dot, partners) in self.dots.iter().zip(&self.partners) {
+            shape::prune(grid, dot.cells, partners)?;
+        }
+        if !self.negative.is_empty() {
+            for &cells in &self.unmarked {
+                shape::prune(grid, cells, &self.unrelated)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Grid, Puzzle};
+
+    /// Whether a relation holds between horizontal or vertical neighbours of
+    /// a solution, other than a given pair.
+    fn holds_unmarked(sol: &Grid<u8>, marked: [(usize, usize); 2], marker: Marker) -> bool {
+        let size = sol.size();
+        (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .flat_map(|(row, col)| [[(row, col), (row, col + 1)], [(row, col), (row + 1, col)]])
+            .filter(|&[_, (row, col)]| row < size && col < size)
+            .filter(|&cells| cells != marked)
+            .any(|[(r1, c1), (r2, c2)]| marker.holds(sol[r1][c1], sol[r2][c2]))
+    }
+
+    #[test]
+    fn marked_relations() {
+        let dots = vec![
+            Dot::new(Marker::White, (0, 0), (0, 1)),
+            Dot::new(Marker::Black, (1, 1), (2, 1)),
+            Dot::new(Marker::V, (3, 2), (3, 3)),
+        ];
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| {
+                sol[0][0].abs_diff(sol[0][1]) == 1
+                    && (sol[1][1] == 2 * sol[2][1] || sol[2][1] == 2 * sol[1][1])
+                    && sol[3][2] + sol[3][3] == 5
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        let sols: Vec<_> = classic
+            .with_constraint(Dots::new(4, dots).unwrap())
+            .solutions()
+            .collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn negative_constraint() {
+        let marked = [(0, 0), (0, 1)];
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| {
+                sol[0][0].abs_diff(sol[0][1]) == 1 && !holds_unmarked(sol, marked, Marker::V)
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        let dots = Dots::new(4, vec![Dot::new(Marker::White, marked[0], marked[1])])
+            .unwrap()
+            .with_negative(Marker::V);
+        let sols: Vec<_> = classic.with_constraint(dots).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn dots_prune_candidates() {
+        let dots = vec![
+            Dot::new(Marker::X, (0, 0), (0, 1)),
+            Dot::new(Marker::Black, (4, 4), (5, 4)),
+        ];
+        let pzl = Puzzle::default().with_constraint(Dots::new(9, dots).unwrap());
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(0, 1).bits(), 0b111_101_111);
+        assert_eq!(grid.candidates(5, 4).bits(), 0b010_101_111);
+    }
+
+    #[test]
+    fn invalid_dots() {
+        let dot = |marker, a, b| vec![Dot::new(marker, a, b)];
+        assert_eq!(
+            Dots::new(4, dot(Marker::V, (0, 3), (0, 4))),
+            Err(ShapeError::OutOfGrid(0, 4))
+        );
+        assert_eq!(
+            Dots::new(4, dot(Marker::V, (0, 0), (1, 1))),
+            Err(ShapeError::NotAdjacent(0))
+        );
+        assert_eq!(
+            Dots::new(4, dot(Marker::X, (0, 0), (0, 1))),
+            Err(ShapeError::ImpossibleClue(0))
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Greater-than signs of a comparison sudoku, between orthogonally adjacent
+/// cells.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{GreaterThan, Puzzle};
+///
+/// // The first cell is larger than the cells to its right and below.
+/// let signs = vec![[(0, 0), (0, 1)], [(0, 0), (1, 0)]];
+/// let pzl = Puzzle::default().with_constraint(GreaterThan::new(9, signs).unwrap());
+/// let sol = pzl.solutions().next().unwrap();
+/// assert!(sol[0][0] > sol[0][1] && sol[0][0] > sol[1][0]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct GreaterThan {
+    size: usize,
+    pairs: Vec<[(usize, usize); 2]>,
+}
+
+impl GreaterThan {
+    /// Build signs in a grid of a given size, each given as the positions of
+    /// the larger cell then the smaller cell, 0-indexed.
+    pub fn new(size: usize, pairs: Vec<[(usize, usize); 2]>) -> Result<Self, ShapeError> {
+        for (i, &[larger, smaller]) in pairs.iter().enumerate() {
+            shape::check_cells(size, &[larger, smaller])?;
+            if larger.0.abs_diff(smaller.0) + larger.1.abs_diff(smaller.1) != 1 {
+                return Err(ShapeError::NotAdjacent(i));
+            }
+        }
+        Ok(Self { size, pairs })
+    }
+
+    /// The signs, as the positions of the larger then the smaller cell.
+    pub fn pairs(&self) -> &[[(usize, usize); 2]] {
+        &self.pairs
+    }
+}
+
+impl Constraint for GreaterThan {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for &[(lrow, lcol), (srow, scol)] in &self.pairs {
+            let floor = shape::min_value(grid.candidates(srow, scol));
+            grid.restrict(lrow, lcol, shape::value_range(floor + 1, 32))?;
+            let ceiling = shape::max_value(grid.candidates(lrow, lcol));
+            grid.restrict(srow, scol, shape::value_range(1, ceiling - 1))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Puzzle};
+
+    #[test]
+    fn signs_order_values() {
+        let pairs = vec![[(0, 0), (0, 1)], [(1, 1), (0, 1)], [(2, 3), (3, 3)]];
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| sol[0][0] > sol[0][1] && sol[1][1] > sol[0][1] && sol[2][3] > sol[3][3])
+            .collect();
+        assert!(!expected.is_empty());
+        let signs = GreaterThan::new(4, pairs).unwrap();
+        let sols: Vec<_> = classic.with_constraint(signs).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn signs_prune_candidates() {
+        // A chain of three increasing cells.
+        let signs = GreaterThan::new(9, vec![[(0, 2), (0, 1)], [(0, 1), (0, 0)]]).unwrap();
+        let pzl = Puzzle::default().with_constraint(signs);
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(0, 0).bits(), 0b001_111_111);
+        assert_eq!(grid.candidates(0, 1).bits(), 0b011_111_110);
+        assert_eq!(grid.candidates(0, 2).bits(), 0b111_111_100);
+    }
+
+    #[test]
+    fn invalid_signs() {
+        assert_eq!(
+            GreaterThan::new(4, vec![[(0, 0), (2, 0)]]),
+            Err(ShapeError::NotAdjacent(0))
+        );
+        assert_eq!(
+            GreaterThan::new(4, vec![[(3, 3), (3, 4)]]),
+            Err(ShapeError::OutOfGrid(3, 4))
+        );
+    }
+}
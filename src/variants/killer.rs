@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::constraint::{Constraint, Contradiction, GridState};
+
+/// A group of cells holding distinct values that add up to a given sum.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Cage {
+    sum: u32,
+    cells: Vec<(usize, usize)>,
+}
+
+impl Cage {
+    /// Create a cage of cells at given `(row, col)` positions, 0-indexed.
+    pub fn new(sum: u32, cells: Vec<(usize, usize)>) -> Self {
+        Self { sum, cells }
+    }
+
+    /// Sum of the values in the cage.
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// Positions of the cells of the cage.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+}
+
+/// Error signaling that cages given to [`Killer::new`] do not fit in the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CageError {
+    /// A cage has no cells, or more cells than there are values.
+    BadCellCount(usize),
+    /// A cell at a given position is outside of the grid.
+    OutOfGrid(usize, usize),
+    /// The cell at a given position belongs to several cages.
+    Overlap(usize, usize),
+    /// No set of distinct values of the grid adds up to the sum of a cage.
+    ImpossibleSum(usize),
+}
+
+impl fmt::Display for CageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CageError::BadCellCount(cage) => write!(f, "cage {cage} has a bad number of cells"),
+            CageError::OutOfGrid(row, col) => {
+                write!(f, "cell at row {row}, column {col} is outside of the grid")
+            }
+            CageError::Overlap(row, col) => {
+                write!(f, "cell at row {row}, column {col} is in several cages")
+            }
+            CageError::ImpossibleSum(cage) => write!(f, "cage {cage} has an impossible sum"),
+        }
+    }
+}
+
+impl std::error::Error for CageError {}
+
+/// Cages of a killer sudoku.  Cells do not have to be all covered by cages,
+/// and the puzzle may have givens as well.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Cage, Killer, Puzzle};
+///
+/// // The first row of a 4x4 grid holds two pairs adding up to 3 and 7.
+/// let cages = vec![
+///     Cage::new(3, vec![(0, 0), (0, 1)]),
+///     Cage::new(7, vec![(0, 2), (0, 3)]),
+/// ];
+/// let killer = Killer::new(4, cages).unwrap();
+/// let pzl = Puzzle::from_arr([[0; 4]; 4]).with_constraint(killer);
+/// for sol in pzl.solutions() {
+///     assert_eq!(sol[0][0] + sol[0][1], 3);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Killer {
+    size: usize,
+    cages: Vec<Cage>,
+    /// Bitmasks of the sets of values that fit each cage.
+    combinations: Vec<Vec<u32>>,
+}
+
+impl Killer {
+    /// Build the cages of a grid of a given size, precomputing the sets of
+    /// values that can fill each of them.
+    pub fn new(size: usize, cages: Vec<Cage>) -> Result<Self, CageError> {
+        let mut seen = vec![false; size * size];
+        for (i, cage) in cages.iter().enumerate() {
+            if cage.cells.is_empty() || cage.cells.len() > size {
+                return Err(CageError::BadCellCount(i));
+            }
+            for &(row, col) in &cage.cells {
+                if row >= size || col >= size {
+                    return Err(CageError::OutOfGrid(row, col));
+                }
+                if std::mem::replace(&mut seen[row * size + col], true) {
+                    return Err(CageError::Overlap(row, col));
+                }
+            }
+        }
+        let mut table = HashMap::new();
+        let combinations = cages
+            .iter()
+            .map(|cage| {
+                table
+                    .entry((cage.cells.len(), cage.sum))
+                    .or_insert_with(|| combinations(size, cage.cells.len(), cage.sum))
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        if let Some(i) = combinations.iter().position(Vec::is_empty) {
+            return Err(CageError::ImpossibleSum(i));
+        }
+        Ok(Self {
+            size,
+            cages,
+            combinations,
+        })
+    }
+
+    /// The cages of the puzzle.
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// Index of the cage containing a given cell, if any.
+    pub fn cage_at(&self, row: usize, col: usize) -> Option<usize> {
+        self.cages
+            .iter()
+            .position(|cage| cage.cells.contains(&(row, col)))
+    }
+}
+
+/// Bitmasks of all the sets of `len` distinct values from 1 to `size` that
+/// add up to `sum`.
+fn combinations(size: usize, len: usize, sum: u32) -> Vec<u32> {
+    fn extend(from: u32, size: u32, len: u32, sum: u32, mask: u32, out: &mut Vec<u32>) {
+        if len == 0 {
+            if sum == 0 {
+                out.push(mask);
+            }
+            return;
+        }
+        // Smallest and largest sums of `len` distinct values from `from`.
+        let min = len * from + len * (len - 1) / 2;
+        let max = len * size - len * (len - 1) / 2;
+        if sum < min || sum > max {
+            return;
+        }
+        for val in from..=size.min(sum) {
+            let mask = mask | (1 << (val - 1));
+            extend(val + 1, size, len - 1, sum - val, mask, out);
+        }
+    }
+    let mut out = Vec::new();
+    extend(1, size as u32, len as u32, sum, 0, &mut out);
+    out
+}
+
+impl Constraint for Killer {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn groups(&self, _size: usize) -> Vec<Vec<(usize, usize)>> {
+        self.cages.iter().map(|cage| cage.cells.clone()).collect()
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for (cage, combinations) in self.cages.iter().zip(&self.combinations) {
+            let candidates: Vec<u32> = cage
+                .cells
+                .iter()
+                .map(|&(row, col)| grid.candidates(row, col))
+                .collect();
+            let pinned = cage
+                .cells
+                .iter()
+                .filter_map(|&(row, col)| grid.value(row, col))
+                .fold(0, |acc, val| acc | (1 << (val.value() - 1)));
+            let reachable = candidates.iter().fold(0, |acc, c| acc | c);
+            // A set of values fits if it contains the pinned values, each cell
+            // has a candidate in it, and each of its values has a cell.
+            let allowed = combinations
+                .iter()
+                .filter(|&&combi| {
+                    combi & pinned == pinned
+                        && combi & reachable == combi
+                        && candidates.iter().all(|c| c & combi != 0)
+                })
+                .fold(0, |acc, combi| acc | combi);
+            if allowed == 0 {
+                return Err(Contradiction);
+            }
+            for &(row, col) in &cage.cells {
+                grid.restrict(row, col, allowed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CandidateSet, Puzzle, Value};
+
+    fn killer_without_givens() -> Puzzle {
+        let cages: &[(u32, &[(usize, usize)])] = &[
+            (11, &[(0, 0), (1, 0)]),
+            (9, &[(0, 1), (1, 1)]),
+            (19, &[(0, 2), (0, 3), (1, 2)]),
+            (3, &[(0, 4), (0, 5)]),
+            (20, &[(0, 6), (0, 7), (1, 6)]),
+            (7, &[(0, 8), (1, 8)]),
+            (15, &[(1, 3), (1, 4), (2, 3)]),
+            (19, &[(1, 5), (2, 5), (2, 6)]),
+            (18, &[(1, 7), (2, 7), (3, 7), (3, 8)]),
+            (10, &[(2, 0), (3, 0)]),
+            (17, &[(2, 1), (2, 2), (3, 1)]),
+            (16, &[(2, 4), (3, 3), (3, 4)]),
+            (3, &[(2, 8)]),
+            (14, &[(3, 2), (4, 2), (4, 3)]),
+            (10, &[(3, 5), (4, 5)]),
+            (12, &[(3, 6), (4, 6), (5, 6)]),
+            (11, &[(4, 0), (5, 0)]),
+            (11, &[(4, 1), (5, 1), (5, 2)]),
+            (18, &[(4, 4), (5, 3), (5, 4)]),
+            (19, &[(4, 7), (5, 7), (5, 8)]),
+            (7, &[(4, 8)]),
+            (5, &[(5, 5), (6, 5)]),
+            (20, &[(6, 0), (6, 1), (7, 0)]),
+            (15, &[(6, 2), (7, 2)]),
+            (14, &[(6, 3), (7, 3), (8, 2), (8, 3)]),
+            (22, &[(6, 4), (7, 4), (7, 5), (8, 5)]),
+            (8, &[(6, 6), (6, 7)]),
+            (15, &[(6, 8), (7, 7), (7, 8)]),
+            (6, &[(7, 1), (8, 0), (8, 1)]),
+            (14, &[(7, 6), (8, 6), (8, 7)]),
+            (9, &[(8, 4)]),
+            (8, &[(8, 8)]),
+        ];
+        let cages = cages
+            .iter()
+            .map(|(sum, cells)| Cage::new(*sum, cells.to_vec()))
+            .collect();
+        Puzzle::default().with_constraint(Killer::new(9, cages).unwrap())
+    }
+
+    #[test]
+    fn killer_single_sol() {
+        let pzl = killer_without_givens();
+        let expected = [
+            [3, 5, 9, 4, 1, 2, 8, 7, 6],
+            [8, 4, 6, 7, 3, 9, 5, 2, 1],
+            [1, 7, 2, 5, 8, 6, 4, 9, 3],
+            [9, 8, 5, 2, 6, 7, 1, 3, 4],
+            [4, 2, 1, 8, 5, 3, 9, 6, 7],
+            [7, 6, 3, 9, 4, 1, 2, 8, 5],
+            [6, 9, 8, 1, 7, 4, 3, 5, 2],
+            [5, 1, 7, 3, 2, 8, 6, 4, 9],
+            [2, 3, 4, 6, 9, 5, 7, 1, 8],
+        ];
+        let mut sols = pzl.solutions();
+        assert_eq!(sols.next().unwrap(), expected);
+        assert!(sols.next().is_none());
+        let pvals = pzl.possible_values();
+        assert!(pvals.iter().flatten().all(|vals| vals.len() == 1));
+    }
+
+    #[test]
+    fn cage_sums_prune_candidates() {
+        let killer = Killer::new(9, vec![Cage::new(4, vec![(0, 0), (0, 1)])]).unwrap();
+        let mut pzl = Puzzle::default().with_constraint(killer);
+        assert_eq!(pzl.constraint::<Killer>().unwrap().cage_at(0, 1), Some(0));
+        assert_eq!(pzl.possible_values()[0][0], CandidateSet::from([1, 3]));
+        pzl.pin(5, 0, Value::new(3));
+        assert_eq!(pzl.possible_values()[0][0], CandidateSet::from([1]));
+        pzl.pin(0, 5, Value::new(1));
+        assert!(pzl.solutions().next().is_none());
+    }
+
+    #[test]
+    fn combinations_of_values() {
+        assert_eq!(combinations(9, 2, 3), [0b11]);
+        assert_eq!(combinations(9, 2, 17), [0b1_1000_0000]);
+        assert_eq!(combinations(9, 3, 10).len(), 4);
+        assert_eq!(combinations(9, 9, 45), [0b1_1111_1111]);
+        assert!(combinations(9, 2, 18).is_empty());
+        assert!(combinations(4, 2, 8).is_empty());
+    }
+
+    #[test]
+    fn invalid_cages() {
+        let cage = |sum, cells: &[(usize, usize)]| Cage::new(sum, cells.to_vec());
+        assert_eq!(
+            Killer::new(4, vec![cage(3, &[])]).unwrap_err(),
+            CageError::BadCellCount(0)
+        );
+        assert_eq!(
+            Killer::new(4, vec![cage(3, &[(0, 0), (0, 4)])]).unwrap_err(),
+            CageError::OutOfGrid(0, 4)
+        );
+        assert_eq!(
+            Killer::new(
+                4,
+                vec![cage(3, &[(0, 0), (0, 1)]), cage(4, &[(0, 1), (1, 1)])]
+            )
+            .unwrap_err(),
+            CageError::Overlap(0, 1)
+        );
+        assert_eq!(
+            Killer::new(
+                4,
+                vec![cage(3, &[(0, 0), (0, 1)]), cage(8, &[(1, 0), (1, 1)])]
+            )
+            .unwrap_err(),
+            CageError::ImpossibleSum(1)
+        );
+    }
+}
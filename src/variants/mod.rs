@@ -0,0 +1,55 @@
+//! Rules of sudoku variants, as [`Constraint`] to add to a [`Puzzle`] with
+//! [`Puzzle::with_constraint`].
+//!
+//! [`Constraint`]: crate::Constraint
+//! [`Puzzle`]: crate::Puzzle
+//! [`Puzzle::with_constraint`]: crate::Puzzle::with_constraint
+mod arrow;
+mod chess;
+mod diagonals;
+mod dots;
+mod inequality;
+mod killer;
+mod parity;
+mod sandwich;
+mod shape;
+mod thermo;
+mod whispers;
+
+pub use arrow::Arrows;
+pub use chess::{AntiKing, AntiKnight};
+pub use diagonals::Diagonals;
+pub use dots::{Dot, Dots, Marker};
+pub use inequality::GreaterThan;
+pub use killer::{Cage, CageError, Killer};
+pub use parity::Parity;
+pub use sandwich::{Sandwich, Sandwiches};
+pub use shape::ShapeError;
+pub use thermo::Thermometers;
+pub use whispers::Whispers;
+
+/// Helpers shared by the tests of the variants.
+#[cfg(test)]
+mod testing {
+    use crate::solgrid::SolutionGrid;
+    use crate::{Grid, Puzzle};
+
+    /// Solutions flattened to rows of values, in sorted order.
+    pub fn sorted(sols: Vec<Grid<u8>>) -> Vec<Vec<u8>> {
+        let mut sols: Vec<Vec<u8>> = sols
+            .iter()
+            .map(|s| s.iter().flatten().copied().collect())
+            .collect();
+        sols.sort();
+        sols
+    }
+
+    /// The grid of a puzzle with its constraints propagated, before any
+    /// search.  Panics if propagation finds the puzzle has no solution.
+    pub fn propagated(pzl: &Puzzle) -> SolutionGrid {
+        let Ok(grid) = SolutionGrid::try_from(pzl) else {
+            panic!("puzzle has solutions");
+        };
+        grid
+    }
+}
@@ -0,0 +1,93 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Bitmask of the odd values.
+const ODD: u32 = 0x5555_5555;
+
+/// Shaded cells of an odd/even sudoku, holding odd or even values.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Parity, Puzzle};
+///
+/// let parity = Parity::new(9, vec![(0, 0)], vec![(0, 1), (0, 2)]).unwrap();
+/// let pzl = Puzzle::default().with_constraint(parity);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert_eq!(sol[0][0] % 2, 1);
+/// assert_eq!((sol[0][1] % 2, sol[0][2] % 2), (0, 0));
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Parity {
+    size: usize,
+    odd: Vec<(usize, usize)>,
+    even: Vec<(usize, usize)>,
+}
+
+impl Parity {
+    /// Build odd and even cells of a grid of a given size, at given
+    /// `(row, col)` positions, 0-indexed.
+    pub fn new(
+        size: usize,
+        odd: Vec<(usize, usize)>,
+        even: Vec<(usize, usize)>,
+    ) -> Result<Self, ShapeError> {
+        shape::check_cells(size, &odd)?;
+        shape::check_cells(size, &even)?;
+        Ok(Self { size, odd, even })
+    }
+
+    /// Cells holding odd values.
+    pub fn odd(&self) -> &[(usize, usize)] {
+        &self.odd
+    }
+
+    /// Cells holding even values.
+    pub fn even(&self) -> &[(usize, usize)] {
+        &self.even
+    }
+}
+
+impl Constraint for Parity {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for (cells, allowed) in [(&self.odd, ODD), (&self.even, !ODD)] {
+            for &(row, col) in cells {
+                grid.restrict(row, col, allowed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::propagated;
+    use crate::Puzzle;
+
+    #[test]
+    fn parity_prunes_candidates() {
+        let parity = Parity::new(9, vec![(0, 0)], vec![(8, 8)]).unwrap();
+        let pzl = Puzzle::default().with_constraint(parity);
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(0, 0).bits(), 0b101_010_101);
+        assert_eq!(grid.candidates(8, 8).bits(), 0b010_101_010);
+        assert_eq!(grid.candidates(4, 4).bits(), 0b111_111_111);
+    }
+
+    #[test]
+    fn too_many_even_cells() {
+        // A row of a 4x4 grid only has two even values.
+        let parity = Parity::new(4, vec![], vec![(0, 0), (0, 1), (0, 3)]).unwrap();
+        let pzl = Puzzle::new(crate::Geometry::new(2, 2)).with_constraint(parity);
+        assert!(pzl.solutions().next().is_none());
+        assert_eq!(
+            Parity::new(4, vec![(4, 0)], vec![]),
+            Err(ShapeError::OutOfGrid(4, 0))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Clue of a sandwich sudoku: the sum of the values between the smallest and
+/// the largest values of a row or column.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Sandwich {
+    is_row: bool,
+    index: usize,
+    sum: u32,
+}
+
+impl Sandwich {
+    /// Clue on a given row, 0-indexed.
+    pub fn row(index: usize, sum: u32) -> Self {
+        Self {
+            is_row: true,
+            index,
+            sum,
+        }
+    }
+
+    /// Clue on a given column, 0-indexed.
+    pub fn column(index: usize, sum: u32) -> Self {
+        Self {
+            is_row: false,
+            index,
+            sum,
+        }
+    }
+
+    /// Whether the clue is on a row rather than a column.
+    pub fn is_row(&self) -> bool {
+        self.is_row
+    }
+
+    /// Index of the row or column of the clue.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Sum of the values between the smallest and largest values.
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// Positions of the cells of the row or column, in order.
+    fn cells(&self, size: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..size).map(move |i| {
+            if self.is_row {
+                (self.index, i)
+            } else {
+                (i, self.index)
+            }
+        })
+    }
+}
+
+/// Sandwich clues on rows and columns.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Puzzle, Sandwich, Sandwiches};
+///
+/// // The 1 and the 9 of the first row are next to each other.
+/// let clues = Sandwiches::new(9, vec![Sandwich::row(0, 0)]).unwrap();
+/// let pzl = Puzzle::default().with_constraint(clues);
+/// let sol = pzl.solutions().next().unwrap();
+/// let one = sol[0].iter().position(|&v| v == 1).unwrap();
+/// let nine = sol[0].iter().position(|&v| v == 9).unwrap();
+/// assert_eq!(one.abs_diff(nine), 1);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Sandwiches {
+    size: usize,
+    clues: Vec<Sandwich>,
+}
+
+impl Sandwiches {
+    /// Build sandwich clues for a grid of a given size.
+    pub fn new(size: usize, clues: Vec<Sandwich>) -> Result<Self, ShapeError> {
+        let max_sum = (2..size as u32).sum();
+        for (i, clue) in clues.iter().enumerate() {
+            if clue.index >= size {
+                let (row, col) = clue.cells(size).next().unwrap_or((clue.index, clue.index));
+                return Err(ShapeError::OutOfGrid(row, col));
+            }
+            if size < 2 || clue.sum > max_sum {
+                return Err(ShapeError::ImpossibleClue(i));
+            }
+        }
+        Ok(Self { size, clues })
+    }
+
+    /// The clues.
+    pub fn clues(&self) -> &[Sandwich] {
+        &self.clues
+    }
+}
+
+impl Constraint for Sandwiches {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        let size = self.size;
+        let (low, high) = (1, 1 << (size - 1));
+        let crust = low | high;
+        for clue in &self.clues {
+            let cells: Vec<_> = clue.cells(size).collect();
+            let cands: Vec<u32> = cells.iter().map(|&(r, c)| grid.candidates(r, c)).collect();
+            let mut allowed = vec![0; size];
+            for (a, b) in (0..size).flat_map(|a| (0..size).map(move |b| (a, b))) {
+                if a == b || cands[a] & low == 0 || cands[b] & high == 0 {
+                    continue;
+                }
+                let (start, end) = (a.min(b), a.max(b));
+                let outside = (0..start).chain(end + 1..size);
+                if outside.clone().any(|i| cands[i] & !crust == 0) {
+                    continue;
+                }
+                let inner: Vec<u32> = (start + 1..end).map(|i| cands[i] & !crust).collect();
+                if inner.contains(&0) {
+                    continue;
+                }
+                let n = inner.len() as u32;
+                let min_sum: u32 = inner.iter().map(|&c| shape::min_value(c)).sum();
+                let max_sum: u32 = inner.iter().map(|&c| shape::max_value(c)).sum();
+                // Bounds for distinct values from 2 to size - 1.
+                let min_distinct = (2..n + 2).sum::<u32>();
+                let max_distinct = (size as u32 - n..size as u32).sum::<u32>();
+                if clue.sum < min_sum.max(min_distinct) || clue.sum > max_sum.min(max_distinct) {
+                    continue;
+                }
+                allowed[a] |= low;
+                allowed[b] |= high;
+                for i in outside {
+                    allowed[i] |= cands[i] & !crust;
+                }
+                for (i, &c) in (start + 1..end).zip(&inner) {
+                    let (min, max) = (shape::min_value(c), shape::max_value(c));
+                    let from = clue.sum.saturating_sub(max_sum - max);
+                    let to = clue.sum.saturating_sub(min_sum - min);
+                    allowed[i] |= c & shape::value_range(from, to);
+                }
+            }
+            for (&(row, col), &mask) in cells.iter().zip(&allowed) {
+                grid.restrict(row, col, mask)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Grid, Puzzle};
+
+    /// Sum between the smallest and largest values of a line.
+    fn sandwich_sum(line: &[u8]) -> u32 {
+        let low = line.iter().position(|&v| v == 1).unwrap();
+        let high = line.iter().position(|&v| v as usize == line.len()).unwrap();
+        let (start, end) = (low.min(high), low.max(high));
+        line[start + 1..end].iter().map(|&v| v as u32).sum()
+    }
+
+    fn column(sol: &Grid<u8>, col: usize) -> Vec<u8> {
+        (0..sol.size()).map(|row| sol[row][col]).collect()
+    }
+
+    #[test]
+    fn sandwich_sums() {
+        let clues = vec![Sandwich::row(0, 5), Sandwich::column(2, 0)];
+        let sandwiches = Sandwiches::new(4, clues).unwrap();
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| sandwich_sum(&sol[0]) == 5 && sandwich_sum(&column(sol, 2)) == 0)
+            .collect();
+        assert!(!expected.is_empty());
+        let sols: Vec<_> = classic.with_constraint(sandwiches).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn sandwiches_prune_candidates() {
+        // 35 is the largest sum: the 1 and the 9 are at both ends.
+        let sandwiches = Sandwiches::new(9, vec![Sandwich::row(4, 35)]).unwrap();
+        let pzl = Puzzle::default().with_constraint(sandwiches);
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(4, 0).bits(), 0b100_000_001);
+        assert_eq!(grid.candidates(4, 4).bits(), 0b011_111_110);
+    }
+
+    #[test]
+    fn invalid_sandwiches() {
+        assert_eq!(
+            Sandwiches::new(9, vec![Sandwich::row(0, 36)]),
+            Err(ShapeError::ImpossibleClue(0))
+        );
+        assert_eq!(
+            Sandwiches::new(9, vec![Sandwich::column(9, 0)]),
+            Err(ShapeError::OutOfGrid(0, 9))
+        );
+    }
+}
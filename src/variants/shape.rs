@@ -0,0 +1,98 @@
+use std::fmt;
+
+use crate::constraint::{Contradiction, GridState};
+
+/// Error signaling that the cells given to a variant constraint do not fit in
+/// the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeError {
+    /// A cell at a given position is outside of the grid.
+    OutOfGrid(usize, usize),
+    /// A shape, given by its index, has too few or too many cells.
+    BadLength(usize),
+    /// A shape, given by its index, has a clue that no values can satisfy.
+    ImpossibleClue(usize),
+    /// A pair of cells, given by its index, is not orthogonally adjacent.
+    NotAdjacent(usize),
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::OutOfGrid(row, col) => {
+                write!(f, "cell at row {row}, column {col} is outside of the grid")
+            }
+            ShapeError::BadLength(shape) => write!(f, "shape {shape} has a bad number of cells"),
+            ShapeError::ImpossibleClue(shape) => write!(f, "shape {shape} has an impossible clue"),
+            ShapeError::NotAdjacent(shape) => write!(f, "shape {shape} has non-adjacent cells"),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Check that all the cells fit in a grid of a given size.
+pub(crate) fn check_cells(size: usize, cells: &[(usize, usize)]) -> Result<(), ShapeError> {
+    match cells.iter().find(|&&(row, col)| row >= size || col >= size) {
+        Some(&(row, col)) => Err(ShapeError::OutOfGrid(row, col)),
+        None => Ok(()),
+    }
+}
+
+/// Smallest value in a non-empty bitmask of candidates.
+pub(crate) fn min_value(candidates: u32) -> u32 {
+    candidates.trailing_zeros() + 1
+}
+
+/// Largest value in a non-empty bitmask of candidates.
+pub(crate) fn max_value(candidates: u32) -> u32 {
+    32 - candidates.leading_zeros()
+}
+
+/// Bitmask of the values from `min` to `max` inclusive, empty if `min` is
+/// larger than `max`.
+pub(crate) fn value_range(min: u32, max: u32) -> u32 {
+    let min = min.max(1);
+    if min > max || min > 32 {
+        0
+    } else {
+        let above_max = if max >= 32 { u32::MAX } else { (1 << max) - 1 };
+        above_max & !((1 << (min - 1)) - 1)
+    }
+}
+
+/// Bitmasks of the values from 1 to `size` paired with each value by a
+/// symmetric relation.  A value is never paired with itself.
+pub(crate) fn partners(size: usize, relation: impl Fn(u8, u8) -> bool) -> Vec<u32> {
+    (1..=size as u8)
+        .map(|a| {
+            (1..=size as u8)
+                .filter(|&b| a != b && relation(a, b))
+                .fold(0, |acc, b| acc | (1 << (b - 1)))
+        })
+        .collect()
+}
+
+/// Keep the candidates of each of two cells that have a partner among the
+/// candidates of the other cell, given the partners of each value by a
+/// symmetric relation.
+pub(crate) fn prune(
+    grid: &mut GridState,
+    cells: [(usize, usize); 2],
+    partners: &[u32],
+) -> Result<(), Contradiction> {
+    for (from, to) in [(cells[0], cells[1]), (cells[1], cells[0])] {
+        let others = grid.candidates(to.0, to.1);
+        let mut candidates = grid.candidates(from.0, from.1);
+        let mut allowed = 0;
+        while candidates != 0 {
+            let val = candidates.trailing_zeros() as usize;
+            if partners[val] & others != 0 {
+                allowed |= 1 << val;
+            }
+            candidates &= candidates - 1;
+        }
+        grid.restrict(from.0, from.1, allowed)?;
+    }
+    Ok(())
+}
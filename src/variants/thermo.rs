@@ -0,0 +1,116 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// Thermometers: values strictly increase along each path, from the bulb.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Puzzle, Thermometers};
+///
+/// let thermos = Thermometers::new(9, vec![vec![(0, 0), (0, 1), (1, 2)]]).unwrap();
+/// let pzl = Puzzle::default().with_constraint(thermos);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert!(sol[0][0] < sol[0][1] && sol[0][1] < sol[1][2]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Thermometers {
+    size: usize,
+    paths: Vec<Vec<(usize, usize)>>,
+}
+
+impl Thermometers {
+    /// Build thermometers in a grid of a given size, each given by the
+    /// positions of its cells from the bulb, 0-indexed.
+    pub fn new(size: usize, paths: Vec<Vec<(usize, usize)>>) -> Result<Self, ShapeError> {
+        for (i, path) in paths.iter().enumerate() {
+            if path.len() < 2 || path.len() > size {
+                return Err(ShapeError::BadLength(i));
+            }
+            shape::check_cells(size, path)?;
+        }
+        Ok(Self { size, paths })
+    }
+
+    /// The paths of the thermometers, from the bulb.
+    pub fn paths(&self) -> &[Vec<(usize, usize)>] {
+        &self.paths
+    }
+}
+
+impl Constraint for Thermometers {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn groups(&self, _size: usize) -> Vec<Vec<(usize, usize)>> {
+        self.paths.clone()
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for path in &self.paths {
+            // Each cell is above the smallest candidate before it...
+            let mut floor = 0;
+            for &(row, col) in path {
+                grid.restrict(row, col, shape::value_range(floor + 1, 32))?;
+                floor = shape::min_value(grid.candidates(row, col));
+            }
+            // ...and below the largest candidate after it.
+            let mut ceiling = 33;
+            for &(row, col) in path.iter().rev() {
+                grid.restrict(row, col, shape::value_range(1, ceiling - 1))?;
+                ceiling = shape::max_value(grid.candidates(row, col));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Puzzle};
+
+    #[test]
+    fn solutions_increase_along_thermometers() {
+        let path = vec![(0, 0), (1, 1), (2, 1)];
+        let thermos = Thermometers::new(4, vec![path.clone()]).unwrap();
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| {
+                path.windows(2)
+                    .all(|w| sol[w[0].0][w[0].1] < sol[w[1].0][w[1].1])
+            })
+            .collect();
+        let sols: Vec<_> = classic.with_constraint(thermos).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn thermometers_prune_candidates() {
+        let thermos = Thermometers::new(9, vec![vec![(0, 0), (0, 1), (0, 2), (1, 2)]]).unwrap();
+        let pzl = Puzzle::default().with_constraint(thermos);
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(0, 0).bits(), 0b000_111_111);
+        assert_eq!(grid.candidates(0, 2).bits(), 0b011_111_100);
+        assert_eq!(grid.candidates(1, 2).bits(), 0b111_111_000);
+    }
+
+    #[test]
+    fn invalid_thermometers() {
+        assert_eq!(
+            Thermometers::new(4, vec![vec![(0, 0)]]),
+            Err(ShapeError::BadLength(0))
+        );
+        assert_eq!(
+            Thermometers::new(4, vec![vec![(0, 0), (0, 1), (0, 2), (0, 3), (1, 3)]]),
+            Err(ShapeError::BadLength(0))
+        );
+        assert_eq!(
+            Thermometers::new(4, vec![vec![(0, 0), (4, 1)]]),
+            Err(ShapeError::OutOfGrid(4, 1))
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::constraint::{Constraint, Contradiction, GridState};
+use crate::variants::shape::{self, ShapeError};
+
+/// German whispers lines: values of neighbouring cells along each line differ
+/// by at least 5.  In a grid of another size than 9, the difference is at
+/// least half the size, rounded up.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_solver::{Puzzle, Whispers};
+///
+/// let whispers = Whispers::new(9, vec![vec![(0, 0), (1, 1), (1, 2)]]).unwrap();
+/// let pzl = Puzzle::default().with_constraint(whispers);
+/// let sol = pzl.solutions().next().unwrap();
+/// assert!(sol[0][0].abs_diff(sol[1][1]) >= 5 && sol[1][1].abs_diff(sol[1][2]) >= 5);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Whispers {
+    size: usize,
+    lines: Vec<Vec<(usize, usize)>>,
+    /// Bitmasks of the values far enough from each value.
+    partners: Vec<u32>,
+}
+
+impl Whispers {
+    /// Build whispers lines in a grid of a given size, each given by the
+    /// positions of its cells in order, 0-indexed.
+    pub fn new(size: usize, lines: Vec<Vec<(usize, usize)>>) -> Result<Self, ShapeError> {
+        let gap = size.div_ceil(2) as u8;
+        let partners = shape::partners(size, |a, b| a.abs_diff(b) >= gap);
+        for (i, line) in lines.iter().enumerate() {
+            if line.len() < 2 {
+                return Err(ShapeError::BadLength(i));
+            }
+            shape::check_cells(size, line)?;
+            if partners.iter().all(|&p| p == 0) {
+                return Err(ShapeError::ImpossibleClue(i));
+            }
+        }
+        Ok(Self {
+            size,
+            lines,
+            partners,
+        })
+    }
+
+    /// The lines, as the positions of their cells in order.
+    pub fn lines(&self) -> &[Vec<(usize, usize)>] {
+        &self.lines
+    }
+}
+
+impl Constraint for Whispers {
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn propagate(&self, grid: &mut GridState) -> Result<(), Contradiction> {
+        for line in &self.lines {
+            for pair in line.windows(2) {
+                shape::prune(grid, [pair[0], pair[1]], &self.partners)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::testing::{propagated, sorted};
+    use crate::{Geometry, Puzzle};
+
+    #[test]
+    fn whispers_differ() {
+        let line = vec![(0, 0), (1, 1), (1, 2), (2, 2)];
+        let classic = Puzzle::new(Geometry::new(2, 2));
+        let expected: Vec<_> = classic
+            .solutions()
+            .filter(|sol| {
+                line.windows(2)
+                    .all(|w| sol[w[0].0][w[0].1].abs_diff(sol[w[1].0][w[1].1]) >= 2)
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        let whispers = Whispers::new(4, vec![line.clone()]).unwrap();
+        let sols: Vec<_> = classic.with_constraint(whispers).solutions().collect();
+        assert_eq!(sorted(sols), sorted(expected));
+    }
+
+    #[test]
+    fn whispers_prune_candidates() {
+        let whispers = Whispers::new(9, vec![vec![(0, 0), (0, 1)]]).unwrap();
+        let pzl = Puzzle::default().with_constraint(whispers);
+        let grid = propagated(&pzl);
+        assert_eq!(grid.candidates(0, 0).bits(), 0b111_101_111);
+    }
+
+    #[test]
+    fn invalid_whispers() {
+        assert_eq!(
+            Whispers::new(9, vec![vec![(0, 0)]]),
+            Err(ShapeError::BadLength(0))
+        );
+        assert_eq!(
+            Whispers::new(1, vec![vec![(0, 0), (0, 0)]]),
+            Err(ShapeError::ImpossibleClue(0))
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! Batch tool: read puzzles one per line, and write one result per line in
+//! the same order, or generate new puzzles.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use sudoku_solver::{Generator, Geometry, Puzzle, Symmetry};
+
+const USAGE: &str = "\
+Usage: sudoku [COMMAND] [OPTIONS] [FILE]...
+
+Process puzzles given one per line in the 81-character format (or the
+equivalent for other sizes), read from the files or from the standard input.
+Each line of output is the result for a line of input, `invalid` for lines
+that are not puzzles.
+
+Commands:
+  solve          Write the first solution, or `no solution` [default]
+  rate           Write the hardest technique needed to solve each puzzle
+  minimize       Remove givens while each puzzle keeps a single solution
+  canonicalize   Write the canonical form of each puzzle
+  generate       Write new puzzles with a single solution, without FILE
+
+Puzzles without a single solution are reported as `no solution` or
+`multiple solutions` by rate and minimize.
+
+Options:
+  -c, --count <N>          solve: write the number of solutions instead,
+                           `>N` past N; generate: number of puzzles [default: 1]
+  -s, --seed <SEED>        generate: seed of the first puzzle [default: 0]
+  -y, --symmetry <SYM>     generate: none, rot, mirror, or diag [default: none]
+  -z, --size <SIZE>        generate: size of the grid, at most 16 [default: 9]
+  -u, --uniqueness         rate: also use techniques that assume a single
+                           solution, such as unique rectangles
+  -j, --threads <N>        Number of threads [default: all available]
+  -h, --help               Print this help
+
+A FILE of `-` is the standard input.";
+
+/// Number of lines processed together before their results are written.
+const CHUNK_LEN: usize = 4096;
+
+/// Largest grid size for `generate`: minimizing larger grids takes too long.
+const MAX_GENERATED_SIZE: usize = 16;
+
+/// What to write for each puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    /// The first solution.
+    Solve,
+    /// The number of solutions, up to a maximum.
+    Count(usize),
+    /// The hardest technique needed, with uniqueness-based techniques or
+    /// not.
+    Rate(bool),
+    /// The puzzle without the givens it does not need.
+    Minimize,
+    /// The canonical form of the puzzle.
+    Canonicalize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    /// Process the lines of the files.
+    Lines(Mode, Vec<String>),
+    /// Generate a number of puzzles, the i-th one with a seed of `seed + i`.
+    Generate {
+        generator: Generator,
+        seed: u64,
+        count: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    command: Command,
+    threads: usize,
+}
+
+/// The value of an option, parsed from the next argument.
+fn value<T: FromStr>(args: &mut impl Iterator<Item = String>, name: &str) -> Result<T, String> {
+    let value = args.next().ok_or(format!("missing value for {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {name}: {value}"))
+}
+
+fn parse_symmetry(name: &str) -> Result<Symmetry, String> {
+    match name {
+        "none" => Ok(Symmetry::None),
+        "rot" | "rotational" => Ok(Symmetry::Rotational),
+        "mirror" => Ok(Symmetry::Mirror),
+        "diag" | "diagonal" => Ok(Symmetry::Diagonal),
+        _ => Err(format!("unknown symmetry {name}")),
+    }
+}
+
+/// Parse the command line arguments, `None` if help was requested.
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter().peekable();
+    let command = match args.peek().map(String::as_str) {
+        Some(name @ ("solve" | "rate" | "minimize" | "canonicalize" | "generate")) => {
+            let name = name.to_owned();
+            args.next();
+            name
+        }
+        _ => "solve".to_owned(),
+    };
+    let generate = command == "generate";
+    let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut count = None;
+    let mut seed = 0;
+    let mut symmetry = Symmetry::None;
+    let mut geometry = Geometry::CLASSIC;
+    let mut uniqueness = false;
+    let mut files = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-c" | "--count" if generate || command == "solve" => {
+                count = Some(value(&mut args, &arg)?)
+            }
+            "-s" | "--seed" if generate => seed = value(&mut args, &arg)?,
+            "-y" | "--symmetry" if generate => {
+                symmetry = parse_symmetry(&value::<String>(&mut args, &arg)?)?
+            }
+            "-z" | "--size" if generate => {
+                let size = value(&mut args, &arg)?;
+                geometry = Geometry::for_size(size)
+                    .filter(|_| size <= MAX_GENERATED_SIZE)
+                    .ok_or(format!("invalid size {size}"))?;
+            }
+            "-u" | "--uniqueness" if command == "rate" => uniqueness = true,
+            "-j" | "--threads" => match value(&mut args, &arg)? {
+                0 => return Err(format!("{arg} needs at least one thread")),
+                n => threads = n,
+            },
+            "-" if !generate => files.push(arg),
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option {arg} for {command}"));
+            }
+            _ if generate => return Err(format!("generate does not read {arg}")),
+            _ => files.push(arg),
+        }
+    }
+    let command = match command.as_str() {
+        "generate" => Command::Generate {
+            generator: Generator::new(geometry).with_symmetry(symmetry),
+            seed,
+            count: count.unwrap_or(1),
+        },
+        "rate" => Command::Lines(Mode::Rate(uniqueness), files),
+        "minimize" => Command::Lines(Mode::Minimize, files),
+        "canonicalize" => Command::Lines(Mode::Canonicalize, files),
+        _ => Command::Lines(count.map_or(Mode::Solve, Mode::Count), files),
+    };
+    Ok(Some(Options { command, threads }))
+}
+
+/// The result for a line of input.
+fn solve_line(line: &str, mode: Mode) -> String {
+    let Ok(puzzle) = Puzzle::from_line(line) else {
+        return "invalid".to_owned();
+    };
+    let single = |puzzle: &Puzzle| match puzzle.solutions().take(2).count() {
+        0 => Err("no solution".to_owned()),
+        1 => Ok(()),
+        _ => Err("multiple solutions".to_owned()),
+    };
+    match mode {
+        Mode::Solve => match puzzle.solutions().next() {
+            Some(sol) => Puzzle::from_grid(puzzle.geometry(), sol).to_line(),
+            None => "no solution".to_owned(),
+        },
+        Mode::Count(max) => {
+            let count = puzzle.solutions().take(max.saturating_add(1)).count();
+            if count > max {
+                format!(">{max}")
+            } else {
+                count.to_string()
+            }
+        }
+        Mode::Rate(uniqueness) => {
+            let rating = if uniqueness {
+                puzzle.rate_with_uniqueness()
+            } else {
+                puzzle.rate()
+            };
+            match rating {
+                Some(rating) => rating.hardest().to_string(),
+                None => single(&puzzle).unwrap_err(),
+            }
+        }
+        Mode::Minimize => match single(&puzzle) {
+            Ok(()) => puzzle.minimized().to_line(),
+            Err(err) => err,
+        },
+        Mode::Canonicalize => match puzzle.canonical() {
+            Some(canonical) => canonical.to_line(),
+            None => "unsupported".to_owned(),
+        },
+    }
+}
+
+/// Map items to lines with a given number of threads, keeping their order.
+fn map_chunk<T: Sync, F>(items: &[T], threads: usize, f: F) -> Vec<String>
+where
+    F: Fn(&T) -> String + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut results = vec![String::new(); items.len()];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            break done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (i, result) in worker.join().expect("worker thread panicked") {
+                results[i] = result;
+            }
+        }
+    });
+    results
+}
+
+/// Solve lines with a given number of threads, keeping their order.
+fn solve_chunk(lines: &[String], mode: Mode, threads: usize) -> Vec<String> {
+    map_chunk(lines, threads, |line| solve_line(line, mode))
+}
+
+/// Generate puzzles with given seeds and a given number of threads, keeping
+/// their order.
+fn generate_chunk(generator: Generator, seeds: &[u64], threads: usize) -> Vec<String> {
+    map_chunk(seeds, threads, |&seed| {
+        generator.with_seed(seed).generate().to_line()
+    })
+}
+
+fn run(options: &Options) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    let (mode, files) = match &options.command {
+        Command::Lines(mode, files) => (*mode, files),
+        &Command::Generate {
+            generator,
+            seed,
+            count,
+        } => {
+            let count = count as u64;
+            for start in (0..count).step_by(CHUNK_LEN) {
+                let end = count.min(start.saturating_add(CHUNK_LEN as u64));
+                let seeds: Vec<u64> = (start..end).map(|i| seed.wrapping_add(i)).collect();
+                for puzzle in generate_chunk(generator, &seeds, options.threads) {
+                    writeln!(out, "{puzzle}")?;
+                }
+                out.flush()?;
+            }
+            return Ok(());
+        }
+    };
+    let mut chunk = Vec::with_capacity(CHUNK_LEN);
+    let mut flush = |chunk: &mut Vec<String>| -> io::Result<()> {
+        for result in solve_chunk(chunk, mode, options.threads) {
+            writeln!(out, "{result}")?;
+        }
+        chunk.clear();
+        out.flush()
+    };
+    let stdin = ["-".to_owned()];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+    for path in files {
+        let input: Box<dyn BufRead> = if path == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            let file = File::open(path)
+                .map_err(|err| io::Error::new(err.kind(), format!("{path}: {err}")))?;
+            Box::new(BufReader::new(file))
+        };
+        for line in input.lines() {
+            chunk.push(line?);
+            if chunk.len() == CHUNK_LEN {
+                flush(&mut chunk)?;
+            }
+        }
+    }
+    flush(&mut chunk)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("sudoku: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("sudoku: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn args(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn command_line() {
+        let options = args(&["-c", "10", "a.txt", "-", "--threads", "3"])
+            .unwrap()
+            .unwrap();
+        let files = vec!["a.txt".to_owned(), "-".to_owned()];
+        assert_eq!(options.command, Command::Lines(Mode::Count(10), files));
+        assert_eq!(options.threads, 3);
+        assert_eq!(args(&["--help"]), Ok(None));
+        assert!(args(&["-j", "0"]).is_err());
+        assert!(args(&["--count"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn subcommands() {
+        let options = args(&["rate"]).unwrap().unwrap();
+        assert_eq!(
+            options.command,
+            Command::Lines(Mode::Rate(false), Vec::new())
+        );
+        let options = args(&["rate", "-u"]).unwrap().unwrap();
+        assert_eq!(
+            options.command,
+            Command::Lines(Mode::Rate(true), Vec::new())
+        );
+        let options = args(&["canonicalize", "-"]).unwrap().unwrap();
+        let files = vec!["-".to_owned()];
+        assert_eq!(options.command, Command::Lines(Mode::Canonicalize, files));
+        let options = args(&["generate", "--count", "5", "-s", "7", "--symmetry", "rot"])
+            .unwrap()
+            .unwrap();
+        let generator = Generator::new(Geometry::CLASSIC).with_symmetry(Symmetry::Rotational);
+        assert_eq!(
+            options.command,
+            Command::Generate {
+                generator,
+                seed: 7,
+                count: 5
+            }
+        );
+        assert!(args(&["generate", "--size", "0"]).is_err());
+        assert!(args(&["generate", "--size", "16"]).is_ok());
+        assert!(args(&["generate", "--size", "25"]).is_err());
+        assert!(args(&["generate", "--symmetry", "spiral"]).is_err());
+        assert!(args(&["generate", "a.txt"]).is_err());
+        assert!(args(&["minimize", "--seed", "1"]).is_err());
+        assert!(args(&["rate", "--count", "1"]).is_err());
+        assert!(args(&["solve", "--uniqueness"]).is_err());
+    }
+
+    #[test]
+    fn results_per_line() {
+        assert_eq!(solve_line(PUZZLE, Mode::Solve), SOLUTION);
+        assert_eq!(solve_line(PUZZLE, Mode::Count(5)), "1");
+        assert_eq!(solve_line(&"0".repeat(16), Mode::Count(5)), ">5");
+        assert_eq!(solve_line(&"0".repeat(16), Mode::Count(288)), "288");
+        assert_eq!(solve_line("11..............", Mode::Solve), "no solution");
+        assert_eq!(solve_line("11..............", Mode::Count(5)), "0");
+        assert_eq!(solve_line("not a puzzle", Mode::Solve), "invalid");
+        assert_eq!(solve_line(PUZZLE, Mode::Rate(false)), "naked-single");
+        let bug =
+            ".8....2........54..23...1.8.....8.6..3..5.......462....9...5.2.47.9........3.64..";
+        assert_eq!(solve_line(bug, Mode::Rate(false)), "guess");
+        assert_eq!(solve_line(bug, Mode::Rate(true)), "bug-plus-one");
+        assert_eq!(
+            solve_line(&"0".repeat(16), Mode::Rate(false)),
+            "multiple solutions"
+        );
+        assert_eq!(
+            solve_line("11..............", Mode::Minimize),
+            "no solution"
+        );
+        let minimal = solve_line(SOLUTION, Mode::Minimize);
+        assert_eq!(solve_line(&minimal, Mode::Solve), SOLUTION);
+        assert_eq!(solve_line(&minimal, Mode::Minimize), minimal);
+        let canonical = solve_line(PUZZLE, Mode::Canonicalize);
+        assert_eq!(solve_line(&canonical, Mode::Canonicalize), canonical);
+        assert_eq!(
+            solve_line(&"0".repeat(256), Mode::Canonicalize),
+            "unsupported"
+        );
+    }
+
+    #[test]
+    fn chunks_keep_order() {
+        let lines: Vec<String> = (0..50)
+            .map(|i| match i % 3 {
+                0 => PUZZLE.to_owned(),
+                1 => "invalid line".to_owned(),
+                _ => "0".repeat(16),
+            })
+            .collect();
+        let results = solve_chunk(&lines, Mode::Count(1000), 4);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result, ["1", "invalid", "288"][i % 3]);
+        }
+    }
+
+    #[test]
+    fn generated_puzzles_depend_on_seed() {
+        let generator = Generator::new(Geometry::new(2, 2));
+        let puzzles = generate_chunk(generator, &[3, 4, 5], 2);
+        assert_eq!(puzzles[1], generate_chunk(generator, &[4], 1)[0]);
+        for puzzle in puzzles {
+            assert_eq!(solve_line(&puzzle, Mode::Count(2)), "1");
+        }
+    }
+}
@@ -1,7 +1,5 @@
-use std::collections::BTreeSet;
-
 use crate::{counter::CounterUpTo, detached::DetachedSolver};
-use sudoku_solver::{Puzzle, Value};
+use sudoku_solver::{CandidateSet, Diagonals, Edit, Killer, Puzzle, Value};
 
 /// State of application, contains the sudoku puzzle.
 pub struct App {
@@ -36,10 +34,22 @@ pub enum Direction {
 }
 
 impl App {
-    /// This keeps the solution and diagnostics up-to-date with the puzzle.
-    /// This has to be called everytime the puzzle is changed.
-    fn update_solution(&mut self) {
-        self.solver = DetachedSolver::new(self.puzzle.clone(), 1000);
+    /// Create the application for a given puzzle, with the cursor at the
+    /// top-left corner.
+    pub fn new(puzzle: Puzzle) -> Self {
+        Self {
+            puzzle: puzzle.clone(),
+            cur_row: 0,
+            cur_col: 0,
+            solver: DetachedSolver::new(puzzle, 1000),
+        }
+    }
+
+    /// Apply an edit to the puzzle, keeping the solution and diagnostics
+    /// up-to-date with it.  The puzzle should only be changed through this.
+    fn edit(&mut self, edit: Edit) {
+        self.solver = self.solver.after_edit(&self.puzzle, edit, 1000);
+        self.puzzle.apply(edit);
     }
 
     /// Set the value of the puzzle at the cursor position.
@@ -47,8 +57,7 @@ impl App {
         let (row, col) = self.current_pos();
         let val = Value::new(value);
         if self.puzzle.get(row, col) != Some(val) {
-            self.puzzle.pin(row, col, Value::new(value));
-            self.update_solution();
+            self.edit(Edit::Pin(row, col, val));
         }
     }
 
@@ -56,9 +65,45 @@ impl App {
     pub fn unpin_current(&mut self) {
         let (row, col) = self.current_pos();
         if self.puzzle.get(row, col).is_some() {
-            self.puzzle.unpin(row, col);
-            self.update_solution();
+            self.edit(Edit::Unpin(row, col));
+        }
+    }
+
+    /// Unset all the values of the puzzle, keeping its constraints.
+    pub fn clear(&mut self) {
+        let size = self.size();
+        for row in 0..size {
+            for col in 0..size {
+                self.puzzle.unpin(row, col);
+            }
         }
+        self.solver = DetachedSolver::new(self.puzzle.clone(), 1000);
+    }
+
+    /// Number of rows and columns of the puzzle.
+    pub fn size(&self) -> usize {
+        self.puzzle.geometry().size()
+    }
+
+    /// Whether the puzzle follows the Sudoku X rule.
+    pub fn has_diagonals(&self) -> bool {
+        self.puzzle.constraint::<Diagonals>().is_some()
+    }
+
+    /// Add or remove the Sudoku X rule, restarting the solver.
+    pub fn toggle_diagonals(&mut self) {
+        let puzzle = self.puzzle.clone();
+        self.puzzle = if self.has_diagonals() {
+            puzzle.without_constraint::<Diagonals>()
+        } else {
+            puzzle.with_constraint(Diagonals)
+        };
+        self.solver = DetachedSolver::new(self.puzzle.clone(), 1000);
+    }
+
+    /// The cages of the puzzle if this is a killer sudoku.
+    pub fn killer(&self) -> Option<&Killer> {
+        self.puzzle.constraint::<Killer>()
     }
 
     /// Return the current cursor position.
@@ -82,8 +127,8 @@ impl App {
     }
 
     /// All possible values that give a solvable puzzle.
-    pub fn all_vals_at(&mut self, row: usize, col: usize) -> Option<&BTreeSet<u8>> {
-        self.solver.poll_possible_values().map(|arr| &arr[row][col])
+    pub fn all_vals_at(&mut self, row: usize, col: usize) -> Option<CandidateSet> {
+        self.solver.poll_possible_values().map(|arr| arr[row][col])
     }
 
     /// Move the cursor in a given direction.
@@ -112,12 +157,6 @@ impl App {
 
 impl Default for App {
     fn default() -> Self {
-        let puzzle = Puzzle::default();
-        Self {
-            puzzle: puzzle.clone(),
-            cur_row: 0,
-            cur_col: 0,
-            solver: DetachedSolver::new(puzzle, 1000),
-        }
+        Self::new(Puzzle::default())
     }
 }
@@ -1,12 +1,14 @@
 use std::{
-    collections::BTreeSet,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    thread,
 };
 
-use sudoku_solver::{Grid, PossibleValuesFinder, Puzzle, SolutionIterator};
+use sudoku_solver::{
+    CandidateSet, Edit, Grid, PossibleValuesFinder, Puzzle, SearchOutcome, SolutionIterator,
+};
 
 use crate::{
     counter::{count_saturated, CounterUpTo},
@@ -18,9 +20,18 @@ use crate::{
 /// dropped.
 pub struct DetachedSolver {
     keep_going: Arc<AtomicBool>,
-    solution: Threaded<Option<Grid<u8>>>,
-    possible_values: Threaded<Grid<BTreeSet<u8>>>,
-    n_solutions: Threaded<CounterUpTo>,
+    solution: Threaded<SearchOutcome<Option<Grid<u8>>>>,
+    possible_values: Threaded<SearchOutcome<Grid<CandidateSet>>>,
+    n_solutions: Threaded<SearchOutcome<CounterUpTo>>,
+}
+
+/// Attach the outcome of the search performed by `solit` to a value computed
+/// from it, so that an interrupted search is not mistaken for a complete one.
+fn with_outcome<T>(solit: &SolutionIterator, value: T) -> SearchOutcome<T> {
+    solit
+        .outcome()
+        .unwrap_or(SearchOutcome::Completed(()))
+        .map(|()| value)
 }
 
 impl DetachedSolver {
@@ -28,32 +39,81 @@ impl DetachedSolver {
     /// solutions to count.
     pub fn new(puzzle: Puzzle, max_count: usize) -> Self {
         let keep_going = Arc::new(AtomicBool::new(true));
+        let finder = Self::finder(&keep_going);
+        let possible_values = Threaded::spawn({
+            let puzzle = puzzle.clone();
+            move || finder.search(&puzzle)
+        });
+        Self::spawn(puzzle, max_count, keep_going, possible_values)
+    }
+
+    /// Create a new solver for the puzzle obtained by applying `edit` to
+    /// `puzzle`, the puzzle of the current solver.  The possible values found
+    /// by the current solver, if any, are reused to speed up the search.
+    pub fn after_edit(&mut self, puzzle: &Puzzle, edit: Edit, max_count: usize) -> Self {
+        let mut edited = puzzle.clone();
+        edited.apply(edit);
+        let previous = match self.poll_possible_values() {
+            Some(pvs) => pvs.clone(),
+            None => return Self::new(edited, max_count),
+        };
+        let keep_going = Arc::new(AtomicBool::new(true));
+        let finder = Self::finder(&keep_going);
+        let puzzle = puzzle.clone();
+        let possible_values =
+            Threaded::spawn(move || finder.search_after_edit(&puzzle, &previous, edit));
+        Self::spawn(edited, max_count, keep_going, possible_values)
+    }
+
+    fn finder(keep_going: &Arc<AtomicBool>) -> PossibleValuesFinder {
+        let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+        PossibleValuesFinder::with_handle(keep_going.clone()).with_threads(n_threads)
+    }
 
+    /// Spawn the threads looking for a solution and counting solutions.
+    fn spawn(
+        puzzle: Puzzle,
+        max_count: usize,
+        keep_going: Arc<AtomicBool>,
+        possible_values: Threaded<SearchOutcome<Grid<CandidateSet>>>,
+    ) -> Self {
         let mut solit_1 = SolutionIterator::new(&puzzle).with_handle(keep_going.clone());
-        let finder = PossibleValuesFinder::with_handle(keep_going.clone());
         let mut solit_2 = solit_1.clone();
 
         Self {
             keep_going,
-            solution: Threaded::spawn(move || solit_1.next()),
-            possible_values: Threaded::spawn(move || finder.search(&puzzle)),
-            n_solutions: Threaded::spawn(move || count_saturated(&mut solit_2, max_count)),
+            solution: Threaded::spawn(move || {
+                let sol = solit_1.next();
+                with_outcome(&solit_1, sol)
+            }),
+            possible_values,
+            n_solutions: Threaded::spawn(move || {
+                let count = count_saturated(&mut solit_2, max_count);
+                with_outcome(&solit_2, count)
+            }),
         }
     }
 
-    /// Check whether a solution has been found by the solver.
+    /// Check whether a solution has been found by the solver.  Interrupted
+    /// searches are reported as still pending.
     pub fn poll_solution(&mut self) -> Option<&Option<Grid<u8>>> {
-        self.solution.try_join()
+        self.solution
+            .try_join()
+            .and_then(|o| o.as_ref().completed())
     }
 
     /// Check whether the set of possible values has been found by the solver.
-    pub fn poll_possible_values(&mut self) -> Option<&Grid<BTreeSet<u8>>> {
-        self.possible_values.try_join()
+    pub fn poll_possible_values(&mut self) -> Option<&Grid<CandidateSet>> {
+        self.possible_values
+            .try_join()
+            .and_then(|o| o.as_ref().completed())
     }
 
     /// Check whether the number of solutions has been found by the solver.
     pub fn poll_n_solutions(&mut self) -> Option<&CounterUpTo> {
-        self.n_solutions.try_join()
+        self.n_solutions
+            .try_join()
+            .and_then(|o| o.as_ref().completed())
     }
 
     /// Whether all solvers are joined.
@@ -6,11 +6,38 @@ use ratatui::{
     },
     Terminal,
 };
-use std::io;
+use std::str::FromStr;
+use std::{env, fs, io, process};
+use sudoku_solver::{Geometry, Puzzle, Regions};
 use sudoku_tui::app_state::App;
 use sudoku_tui::events::event_loop;
 
+/// The puzzle in the file given as the first argument, in the format of
+/// [`Puzzle::from_str`], or an empty puzzle if there is no argument.
+fn load_puzzle() -> Result<Puzzle, String> {
+    let Some(path) = env::args_os().nth(1) else {
+        return Ok(Puzzle::default());
+    };
+    let path = path.to_string_lossy();
+    let text = fs::read_to_string(&*path).map_err(|err| format!("{path}: {err}"))?;
+    let puzzle = Puzzle::from_str(&text).map_err(|err| format!("{path}: {err}"))?;
+    if puzzle.geometry() != Geometry::CLASSIC {
+        return Err(format!(
+            "{path}: only 9x9 grids with 3x3 boxes are supported"
+        ));
+    }
+    if *puzzle.regions() != Regions::boxes(Geometry::CLASSIC) {
+        return Err(format!("{path}: jigsaw regions are not supported"));
+    }
+    Ok(puzzle)
+}
+
 fn main() -> io::Result<()> {
+    let puzzle = load_puzzle().unwrap_or_else(|err| {
+        eprintln!("sudoku-tui: {err}");
+        process::exit(1);
+    });
+
     // setup terminal
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -18,7 +45,7 @@ fn main() -> io::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut term = Terminal::new(backend)?;
 
-    let app = App::default();
+    let app = App::new(puzzle);
     let res = event_loop(&mut term, app);
 
     // restore terminal
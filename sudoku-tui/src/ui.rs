@@ -7,6 +7,8 @@ use ratatui::{
     Frame,
 };
 
+use sudoku_solver::{Diagonals, Grid, Killer};
+
 use crate::{
     app_state::{App, CellValue},
     counter::CounterUpTo,
@@ -16,6 +18,7 @@ enum CellKind {
     Current,
     Pinned,
     UniqueSol,
+    Diagonal,
 }
 
 /// All the styles.
@@ -25,13 +28,73 @@ fn get_style(kind: CellKind) -> Style {
         CellKind::Current => stl.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
         CellKind::Pinned => stl.fg(Color::Green).add_modifier(Modifier::BOLD),
         CellKind::UniqueSol => stl.fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        CellKind::Diagonal => stl.bg(Color::DarkGray),
+    }
+}
+
+/// Background colors of the cages of a killer sudoku.
+const CAGE_COLORS: [Color; 5] = [
+    Color::Indexed(17),
+    Color::Indexed(22),
+    Color::Indexed(52),
+    Color::Indexed(53),
+    Color::Indexed(58),
+];
+
+/// Pick a background color for each cage, such that cages sharing an edge
+/// have different colors as far as possible.
+fn cage_colors(killer: &Killer) -> Vec<Color> {
+    let mut colors: Vec<usize> = Vec::new();
+    for cage in killer.cages() {
+        let used: Vec<usize> = cage
+            .cells()
+            .iter()
+            .flat_map(|&(row, col)| {
+                [
+                    (row + 1, col),
+                    (row, col + 1),
+                    (row.wrapping_sub(1), col),
+                    (row, col.wrapping_sub(1)),
+                ]
+            })
+            .filter_map(|(row, col)| killer.cage_at(row, col))
+            .filter_map(|other| colors.get(other).copied())
+            .collect();
+        let color = (0..CAGE_COLORS.len())
+            .find(|c| !used.contains(c))
+            .unwrap_or(colors.len() % CAGE_COLORS.len());
+        colors.push(color);
     }
+    colors.into_iter().map(|c| CAGE_COLORS[c]).collect()
+}
+
+/// How a cell of a killer sudoku cage is drawn.
+#[derive(Copy, Clone)]
+struct CageCell {
+    color: Color,
+    /// The sum of the cage, only shown in its top-left cell.
+    sum: Option<u32>,
+}
+
+/// Write a number with superscript digits.
+fn superscript(n: u32) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    n.to_string()
+        .chars()
+        .map(|d| DIGITS[d.to_digit(10).unwrap() as usize])
+        .collect()
 }
 
 /// Build the cell content at a given position.
-fn cell_at(app: &mut App, row: usize, col: usize) -> Cell<'static> {
+fn cell_at(app: &mut App, row: usize, col: usize, cage: Option<CageCell>) -> Cell<'static> {
     let mut style = Style::default();
-    let text = match app.value_at(row, col) {
+    if app.has_diagonals() && Diagonals::contains(app.size(), row, col) {
+        style = style.patch(get_style(CellKind::Diagonal));
+    }
+    if let Some(cage) = cage {
+        style = style.bg(cage.color);
+    }
+    let mut text = match app.value_at(row, col) {
         CellValue::Pinned(v) => {
             style = style.patch(get_style(CellKind::Pinned));
             format!(" {v} ")
@@ -46,6 +109,9 @@ fn cell_at(app: &mut App, row: usize, col: usize) -> Cell<'static> {
         }
         CellValue::NoSolution | CellValue::Pending => " . ".to_owned(),
     };
+    if let Some(sum) = cage.and_then(|cage| cage.sum) {
+        text = format!("{:<2}{}", superscript(sum), text.trim());
+    }
     if (row, col) == app.current_pos() {
         style = style.patch(get_style(CellKind::Current));
     }
@@ -97,8 +163,8 @@ impl UiLayout {
         let help_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(heights[0]),
-                Constraint::Length(height - heights[0]),
+                Constraint::Length(heights[0] - 1),
+                Constraint::Length(height - heights[0] + 1),
             ])
             .split(chunks[2]);
 
@@ -126,6 +192,21 @@ struct SudokuTable<'a> {
 
 impl<'a> SudokuTable<'a> {
     fn new(app: &mut App) -> Self {
+        let size = app.size();
+        let cages = match app.killer() {
+            Some(killer) => {
+                let colors = cage_colors(killer);
+                Grid::from_fn(size, |row, col| {
+                    let index = killer.cage_at(row, col)?;
+                    let cage = &killer.cages()[index];
+                    Some(CageCell {
+                        color: colors[index],
+                        sum: (cage.cells().iter().min() == Some(&(row, col))).then(|| cage.sum()),
+                    })
+                })
+            }
+            None => Grid::filled(size, None),
+        };
         let table = Table::default().rows((0..11).map(|ir| {
             let row = ir - ir / 4;
             if ir == 3 || ir == 7 {
@@ -136,7 +217,7 @@ impl<'a> SudokuTable<'a> {
                     if ic == 3 || ic == 7 {
                         Cell::default()
                     } else {
-                        cell_at(app, row, col)
+                        cell_at(app, row, col, cages[row][col])
                     }
                 }))
             }
@@ -154,7 +235,7 @@ impl<'a> SudokuTable<'a> {
 
 impl Widget for SudokuTable<'_> {
     fn render(self, area: Rect, buf: &mut buffer::Buffer) {
-        let blk_pad = self.block.is_some().then(|| 2).unwrap_or(0);
+        let blk_pad = if self.block.is_some() { 2 } else { 0 };
         let mut inner = area;
         inner.width = 39;
         inner.height = 11;
@@ -169,7 +250,7 @@ impl Widget for SudokuTable<'_> {
         let mut widths = [Constraint::Length(3); 11];
         widths[3] = Constraint::Length(1);
         widths[7] = Constraint::Length(1);
-        self.table.widths(&widths).render(inner, buf);
+        self.table.widths(widths).render(inner, buf);
     }
 }
 
@@ -186,8 +267,13 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             return;
         }
     };
+    let title = if app.has_diagonals() {
+        "Sudoku X"
+    } else {
+        "Sudoku"
+    };
     f.render_widget(
-        SudokuTable::new(app).block(Block::default().title("Sudoku").borders(Borders::ALL)),
+        SudokuTable::new(app).block(Block::default().title(title).borders(Borders::ALL)),
         layout.grid,
     );
 
@@ -208,7 +294,17 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         CellValue::Pending => "Solver is still running...".to_owned(),
         CellValue::NoSolution => "No solution.".to_owned(),
     })
-    .block(Block::default().borders(Borders::ALL));
+    .block(
+        match app
+            .killer()
+            .and_then(|k| Some(k.cages()[k.cage_at(row, col)?].sum()))
+        {
+            Some(sum) => Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Cage of {sum}")),
+            None => Block::default().borders(Borders::ALL),
+        },
+    );
     f.render_widget(all_sols_par, layout.possibilities);
 
     let n_sols_par = Paragraph::new(match app.n_solutions() {
@@ -236,7 +332,11 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             Span::styled("<0, Space, Suppr>", bold),
             Span::raw(": unpin"),
         ]),
-        Line::from(vec![Span::styled("<c>", bold), Span::raw(": clear")]),
+        Line::from(vec![Span::styled("<c>", bold), Span::raw(": clear values")]),
+        Line::from(vec![
+            Span::styled("<x>", bold),
+            Span::raw(": toggle diagonals"),
+        ]),
         Line::from(vec![Span::styled("<q>", bold), Span::raw(": quit")]),
     ];
     let cmd_par =
@@ -257,6 +357,10 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             Span::raw(": only one possibility"),
         ]),
         Line::from(vec![Span::raw(" 4 : several possibilities")]),
+        Line::from(vec![
+            Span::styled(" 5 ", get_style(CellKind::Diagonal)),
+            Span::raw(": on a diagonal"),
+        ]),
     ];
     let lgd_par =
         Paragraph::new(lgd_text).block(Block::default().borders(Borders::ALL).title("Legend"));